@@ -1,26 +1,126 @@
+use core::cmp::{max, min};
 use core::marker::PhantomData;
 
 use embedded_graphics::{
-    prelude::{DrawTarget, IntoStorage, PixelColor, Point, Size},
-    primitives::Rectangle,
+    draw_target::ColorConverted,
+    prelude::{Dimensions, DrawTarget, IntoStorage, OriginDimensions, PixelColor, Point, Size},
+    primitives::{Circle, Rectangle},
 };
 
 pub use buffered::*;
+pub use clock::*;
+pub use color_mapped::*;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use drm::*;
 pub use fb::*;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use fbdev::*;
+pub use flipped::*;
 pub use flushing::*;
+pub use instrumented::*;
+pub use letterboxed::*;
+pub use masked::*;
 pub use owned::*;
+#[cfg(feature = "std")]
+pub use rfb::*;
+pub use rle::*;
 pub use rotated::*;
+pub use round_clipped::*;
 pub use scaled::*;
+pub use shared::*;
+pub use split::*;
+pub use sprite::*;
+#[cfg(feature = "embedded-io")]
+pub use stream::*;
+pub use throttled::*;
+pub use tiled::*;
+pub use transformed::*;
 
 mod buffered;
+mod clock;
+mod color_mapped;
+#[cfg(all(feature = "std", target_os = "linux"))]
+mod drm;
 mod fb;
+#[cfg(all(feature = "std", target_os = "linux"))]
+mod fbdev;
+mod flipped;
 mod flushing;
+mod instrumented;
+mod letterboxed;
+mod masked;
 mod owned;
+#[cfg(feature = "std")]
+mod rfb;
+mod rle;
 mod rotated;
+mod round_clipped;
 mod scaled;
+mod shared;
+mod split;
+mod sprite;
+#[cfg(feature = "embedded-io")]
+mod stream;
+mod throttled;
+mod tiled;
+mod transformed;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Refresh {
+    Partial,
+    Full,
+}
 
 pub trait Flushable: DrawTarget {
     fn flush(&mut self) -> Result<(), Self::Error>;
+
+    fn flush_with(&mut self, _refresh: Refresh) -> Result<(), Self::Error> {
+        self.flush()
+    }
+
+    fn flush_area(&mut self, _area: &Rectangle, refresh: Refresh) -> Result<(), Self::Error> {
+        self.flush_with(refresh)
+    }
+}
+
+pub trait CoordinateMapping {
+    fn map_to_logical(&self, point: Point) -> Option<Point>;
+
+    fn map_to_physical(&self, point: Point) -> Option<Point>;
+}
+
+pub trait DisplayMapping {
+    fn map_from_display(&self, point: Point) -> Option<Point>;
+
+    fn map_to_display(&self, point: Point) -> Option<Point>;
+}
+
+impl<T> DisplayMapping for T
+where
+    T: OriginDimensions,
+{
+    fn map_from_display(&self, point: Point) -> Option<Point> {
+        Some(point)
+    }
+
+    fn map_to_display(&self, point: Point) -> Option<Point> {
+        Some(point)
+    }
+}
+
+impl<'a, T, C> CoordinateMapping for ColorConverted<'a, T, C>
+where
+    T: DrawTarget,
+    C: PixelColor + Into<T::Color>,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
 }
 
 pub trait DrawTargetExt2: DrawTarget + Sized {
@@ -28,6 +128,10 @@ pub trait DrawTargetExt2: DrawTarget + Sized {
 
     fn scaled(&mut self, size: Size) -> Scaled<'_, Self>;
 
+    fn letterboxed(&mut self, size: Size, border: Self::Color) -> Letterboxed<'_, Self>;
+
+    fn transformed(&mut self, transform: &AffineTransform) -> Transformed<'_, Self>;
+
     fn flushing<F: FnMut(&mut Self) -> Result<(), Self::Error>>(
         &mut self,
         flusher: F,
@@ -35,6 +139,30 @@ pub trait DrawTargetExt2: DrawTarget + Sized {
 
     #[allow(clippy::type_complexity)]
     fn noop_flushing(&mut self) -> Flushing<'_, Self, fn(&mut Self) -> Result<(), Self::Error>>;
+
+    fn color_mapped<'a, M>(&'a mut self, map: &'a M) -> ColorMapped<'a, Self, M>
+    where
+        M: ColorMap<Self::Color>;
+
+    fn inverted(&mut self) -> ColorMapped<'_, Self, Invert>
+    where
+        Invert: ColorMap<Self::Color>;
+
+    fn grayscale(&mut self) -> ColorMapped<'_, Self, Grayscale>
+    where
+        Grayscale: ColorMap<Self::Color>;
+
+    fn color_lut<'a>(&'a mut self, lut: &'a Lut) -> ColorMapped<'a, Self, Lut>
+    where
+        Lut: ColorMap<Self::Color>;
+
+    fn masked<'a, M>(&'a mut self, mask: &'a M) -> Masked<'a, Self, M>
+    where
+        M: PixelMask<Self::Color>;
+
+    fn round_clipped(&mut self) -> RoundClipped<'_, Self, Circle>;
+
+    fn span_clipped<R: RowSpans>(&mut self, spans: R) -> RoundClipped<'_, Self, R>;
 }
 
 impl<T> DrawTargetExt2 for T
@@ -49,6 +177,14 @@ where
         Scaled::new(self, size)
     }
 
+    fn letterboxed(&mut self, size: Size, border: Self::Color) -> Letterboxed<'_, Self> {
+        Letterboxed::new(self, Letterbox::new(size, border))
+    }
+
+    fn transformed(&mut self, transform: &AffineTransform) -> Transformed<'_, Self> {
+        Transformed::new(self, transform)
+    }
+
     fn flushing<F: FnMut(&mut Self) -> Result<(), Self::Error>>(
         &mut self,
         flusher: F,
@@ -59,6 +195,49 @@ where
     fn noop_flushing(&mut self) -> Flushing<'_, Self, fn(&mut Self) -> Result<(), Self::Error>> {
         Flushing::noop(self)
     }
+
+    fn color_mapped<'a, M>(&'a mut self, map: &'a M) -> ColorMapped<'a, Self, M>
+    where
+        M: ColorMap<Self::Color>,
+    {
+        ColorMapped::new(self, map)
+    }
+
+    fn inverted(&mut self) -> ColorMapped<'_, Self, Invert>
+    where
+        Invert: ColorMap<Self::Color>,
+    {
+        ColorMapped::new(self, &Invert(true))
+    }
+
+    fn grayscale(&mut self) -> ColorMapped<'_, Self, Grayscale>
+    where
+        Grayscale: ColorMap<Self::Color>,
+    {
+        ColorMapped::new(self, &Grayscale(true))
+    }
+
+    fn color_lut<'a>(&'a mut self, lut: &'a Lut) -> ColorMapped<'a, Self, Lut>
+    where
+        Lut: ColorMap<Self::Color>,
+    {
+        ColorMapped::new(self, lut)
+    }
+
+    fn masked<'a, M>(&'a mut self, mask: &'a M) -> Masked<'a, Self, M>
+    where
+        M: PixelMask<Self::Color>,
+    {
+        Masked::new(self, mask)
+    }
+
+    fn round_clipped(&mut self) -> RoundClipped<'_, Self, Circle> {
+        RoundClipped::inscribed(self)
+    }
+
+    fn span_clipped<R: RowSpans>(&mut self, spans: R) -> RoundClipped<'_, Self, R> {
+        RoundClipped::new(self, spans)
+    }
 }
 
 pub trait OwnedDrawTargetExt: DrawTarget + Sized {
@@ -76,6 +255,38 @@ pub trait OwnedDrawTargetExt: DrawTarget + Sized {
 
     fn owned_scaled(self, size: Size) -> Owned<ScaledT<Self>>;
 
+    fn owned_letterboxed(
+        self,
+        size: Size,
+        border: Self::Color,
+    ) -> Owned<LetterboxedT<Self, Self::Color>>;
+
+    fn owned_transformed(self, transform: &AffineTransform) -> Owned<TransformedT<Self>>;
+
+    fn owned_color_mapped<M>(self, map: M) -> Owned<ColorMappedT<Self, M>>
+    where
+        M: ColorMap<Self::Color>;
+
+    fn owned_inverted(self) -> Owned<ColorMappedT<Self, Invert>>
+    where
+        Invert: ColorMap<Self::Color>;
+
+    fn owned_grayscale(self) -> Owned<ColorMappedT<Self, Grayscale>>
+    where
+        Grayscale: ColorMap<Self::Color>;
+
+    fn owned_color_lut(self, lut: Lut) -> Owned<ColorMappedT<Self, Lut>>
+    where
+        Lut: ColorMap<Self::Color>;
+
+    fn owned_masked<M>(self, mask: M) -> Owned<MaskedT<Self, M>>
+    where
+        M: PixelMask<Self::Color>;
+
+    fn owned_round_clipped(self) -> Owned<RoundClippedT<Self, Circle>>;
+
+    fn owned_span_clipped<R: RowSpans>(self, spans: R) -> Owned<RoundClippedT<Self, R>>;
+
     fn owned_flushing<F: FnMut(&mut Self) -> Result<(), Self::Error> + Send + Clone + 'static>(
         self,
         flusher: F,
@@ -96,9 +307,45 @@ pub trait OwnedDrawTargetExt: DrawTarget + Sized {
         self,
         draw_buf: &'a mut [u8],
         reference_buf: &'a mut [u8],
-    ) -> Buffered<'a, Self>
+    ) -> Buffered<Self, PackedFramebuffer<'a, Self::Color>>
     where
         Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>;
+
+    fn owned_buffered_area<'a>(
+        self,
+        area: &Rectangle,
+        draw_buf: &'a mut [u8],
+        reference_buf: &'a mut [u8],
+    ) -> Buffered<Self, PackedFramebuffer<'a, Self::Color>>
+    where
+        Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>;
+
+    fn owned_buffered_rle<'a>(
+        self,
+        draw_buf: &'a mut [u8],
+        compressed_buf: &'a mut [u8],
+    ) -> Buffered<Self, PackedFramebuffer<'a, Self::Color>, RleFramebuffer<'a, Self::Color>>
+    where
+        Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>;
+
+    fn owned_buffered_with<B, R>(self, current: B, reference: R) -> Buffered<Self, B, R>
+    where
+        Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+        B: Framebuffer<Color = Self::Color>,
+        R: ReferenceBuffer<Color = Self::Color>;
+
+    fn owned_tiled(self, strip_buf: &mut [Self::Color]) -> Tiled<'_, Self>;
+
+    fn owned_instrumented<C, O>(self, clock: C, observer: O) -> Instrumented<Self, C, O>
+    where
+        Self: Flushable,
+        C: Clock,
+        O: FlushObserver;
+
+    fn owned_throttled<C>(self, clock: C, max_flushes_per_sec: u32) -> Throttled<Self, C>
+    where
+        Self: Flushable,
+        C: Clock;
 }
 
 impl<T> OwnedDrawTargetExt for T
@@ -132,6 +379,63 @@ where
         ScaledT(self, size).into_owned()
     }
 
+    fn owned_letterboxed(
+        self,
+        size: Size,
+        border: Self::Color,
+    ) -> Owned<LetterboxedT<Self, Self::Color>> {
+        LetterboxedT(self, Letterbox::new(size, border)).into_owned()
+    }
+
+    fn owned_transformed(self, transform: &AffineTransform) -> Owned<TransformedT<Self>> {
+        TransformedT(self, *transform).into_owned()
+    }
+
+    fn owned_color_mapped<M>(self, map: M) -> Owned<ColorMappedT<Self, M>>
+    where
+        M: ColorMap<Self::Color>,
+    {
+        ColorMappedT(self, map).into_owned()
+    }
+
+    fn owned_inverted(self) -> Owned<ColorMappedT<Self, Invert>>
+    where
+        Invert: ColorMap<Self::Color>,
+    {
+        self.owned_color_mapped(Invert(true))
+    }
+
+    fn owned_grayscale(self) -> Owned<ColorMappedT<Self, Grayscale>>
+    where
+        Grayscale: ColorMap<Self::Color>,
+    {
+        self.owned_color_mapped(Grayscale(true))
+    }
+
+    fn owned_color_lut(self, lut: Lut) -> Owned<ColorMappedT<Self, Lut>>
+    where
+        Lut: ColorMap<Self::Color>,
+    {
+        self.owned_color_mapped(lut)
+    }
+
+    fn owned_masked<M>(self, mask: M) -> Owned<MaskedT<Self, M>>
+    where
+        M: PixelMask<Self::Color>,
+    {
+        MaskedT(self, mask).into_owned()
+    }
+
+    fn owned_round_clipped(self) -> Owned<RoundClippedT<Self, Circle>> {
+        let circle = inscribed_circle(&self.bounding_box());
+
+        RoundClippedT(self, circle).into_owned()
+    }
+
+    fn owned_span_clipped<R: RowSpans>(self, spans: R) -> Owned<RoundClippedT<Self, R>> {
+        RoundClippedT(self, spans).into_owned()
+    }
+
     fn owned_flushing<F: FnMut(&mut Self) -> Result<(), Self::Error> + Send + Clone + 'static>(
         self,
         flusher: F,
@@ -155,10 +459,148 @@ where
         self,
         draw_buf: &'a mut [u8],
         reference_buf: &'a mut [u8],
-    ) -> Buffered<'a, Self>
+    ) -> Buffered<Self, PackedFramebuffer<'a, Self::Color>>
     where
         Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
     {
         Buffered::new(draw_buf, reference_buf, self)
     }
+
+    fn owned_buffered_area<'a>(
+        self,
+        area: &Rectangle,
+        draw_buf: &'a mut [u8],
+        reference_buf: &'a mut [u8],
+    ) -> Buffered<Self, PackedFramebuffer<'a, Self::Color>>
+    where
+        Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    {
+        Buffered::with_area(
+            PackedFramebuffer::new(draw_buf, area.size.width as _, area.size.height as _),
+            PackedFramebuffer::new(reference_buf, area.size.width as _, area.size.height as _),
+            self,
+            area,
+        )
+    }
+
+    fn owned_buffered_rle<'a>(
+        self,
+        draw_buf: &'a mut [u8],
+        compressed_buf: &'a mut [u8],
+    ) -> Buffered<Self, PackedFramebuffer<'a, Self::Color>, RleFramebuffer<'a, Self::Color>>
+    where
+        Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    {
+        let size = self.bounding_box().size;
+
+        Buffered::with_framebuffers(
+            PackedFramebuffer::new(draw_buf, size.width as _, size.height as _),
+            RleFramebuffer::new(compressed_buf),
+            self,
+        )
+    }
+
+    fn owned_buffered_with<B, R>(self, current: B, reference: R) -> Buffered<Self, B, R>
+    where
+        Self::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+        B: Framebuffer<Color = Self::Color>,
+        R: ReferenceBuffer<Color = Self::Color>,
+    {
+        Buffered::with_framebuffers(current, reference, self)
+    }
+
+    fn owned_tiled(self, strip_buf: &mut [Self::Color]) -> Tiled<'_, Self> {
+        Tiled::new(strip_buf, self)
+    }
+
+    fn owned_instrumented<C, O>(self, clock: C, observer: O) -> Instrumented<Self, C, O>
+    where
+        Self: Flushable,
+        C: Clock,
+        O: FlushObserver,
+    {
+        Instrumented::new(self, clock, observer)
+    }
+
+    fn owned_throttled<C>(self, clock: C, max_flushes_per_sec: u32) -> Throttled<Self, C>
+    where
+        Self: Flushable,
+        C: Clock,
+    {
+        Throttled::new(self, clock, max_flushes_per_sec)
+    }
+}
+
+pub(crate) fn identity_mapping<D>(target: &D, point: Point) -> Option<Point>
+where
+    D: Dimensions,
+{
+    target.bounding_box().contains(point).then_some(point)
+}
+
+pub(crate) fn union(rect1: &Rectangle, rect2: &Rectangle) -> Rectangle {
+    let bottom_right1 = rect1.top_left + rect1.size;
+    let bottom_right2 = rect2.top_left + rect2.size;
+
+    let top_left = Point::new(
+        min(rect1.top_left.x, rect2.top_left.x),
+        min(rect1.top_left.y, rect2.top_left.y),
+    );
+
+    let bottom_right = Point::new(
+        max(bottom_right1.x, bottom_right2.x),
+        max(bottom_right1.y, bottom_right2.y),
+    );
+
+    Rectangle::new(
+        top_left,
+        Size::new(
+            (bottom_right.x - top_left.x) as _,
+            (bottom_right.y - top_left.y) as _,
+        ),
+    )
+}
+
+pub(crate) fn surrounding(area: &Rectangle, inside: &Rectangle) -> impl Iterator<Item = Rectangle> {
+    let rects = if inside.is_zero_sized() {
+        [
+            *area,
+            Rectangle::zero(),
+            Rectangle::zero(),
+            Rectangle::zero(),
+        ]
+    } else {
+        let bottom_right = area.top_left + area.size;
+        let inside_bottom_right = inside.top_left + inside.size;
+
+        [
+            Rectangle::new(
+                area.top_left,
+                Size::new(area.size.width, (inside.top_left.y - area.top_left.y) as _),
+            ),
+            Rectangle::new(
+                Point::new(area.top_left.x, inside_bottom_right.y),
+                Size::new(
+                    area.size.width,
+                    (bottom_right.y - inside_bottom_right.y) as _,
+                ),
+            ),
+            Rectangle::new(
+                Point::new(area.top_left.x, inside.top_left.y),
+                Size::new(
+                    (inside.top_left.x - area.top_left.x) as _,
+                    inside.size.height,
+                ),
+            ),
+            Rectangle::new(
+                Point::new(inside_bottom_right.x, inside.top_left.y),
+                Size::new(
+                    (bottom_right.x - inside_bottom_right.x) as _,
+                    inside.size.height,
+                ),
+            ),
+        ]
+    };
+
+    rects.into_iter().filter(|rect| !rect.is_zero_sized())
 }
@@ -1,16 +1,142 @@
-use embedded_graphics::prelude::{DrawTarget, IntoStorage, OriginDimensions, PixelColor, Size};
+use core::convert::Infallible;
+use core::iter::from_fn;
+use core::marker::PhantomData;
+
+use embedded_graphics::draw_target::DrawTargetExt;
+use embedded_graphics::image::Image;
+use embedded_graphics::prelude::{
+    Dimensions, DrawTarget, Drawable, IntoStorage, OriginDimensions, PixelColor, Point, PointsIter,
+    Size,
+};
 use embedded_graphics::primitives::Rectangle;
 use embedded_graphics::Pixel;
 
-use super::{Flushable, PackedFramebuffer};
+use super::{
+    surrounding, union, DrawTargetExt2, Flushable, Framebuffer, PackedFramebuffer, PixelMask,
+    Refresh, RowSpans, Sprite,
+};
+
+pub trait ReferenceBuffer {
+    type Color: PixelColor + IntoStorage<Storage = u8> + From<u8>;
+
+    fn apply<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>;
+
+    fn apply_area<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        _area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.apply(current, to)
+    }
+
+    fn diff_area<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>;
+
+    fn sync(&mut self, current: &PackedFramebuffer<'_, Self::Color>);
+}
 
-pub struct Buffered<'a, T>
+impl<B> ReferenceBuffer for B
 where
-    T: DrawTarget,
+    B: Framebuffer,
+    B::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
 {
-    current: PackedFramebuffer<'a, T::Color>,
-    reference: PackedFramebuffer<'a, T::Color>,
+    type Color = B::Color;
+
+    fn apply<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.packed().apply(current, to)
+    }
+
+    fn apply_area<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.packed().apply_area(current, area, to)
+    }
+
+    fn diff_area<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.packed().diff_area(current, area, to)
+    }
+
+    fn sync(&mut self, current: &PackedFramebuffer<'_, Self::Color>) {
+        self.packed().bytes_mut().copy_from_slice(current.bytes());
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bitplane {
+    Msb,
+    Lsb,
+}
+
+impl Bitplane {
+    pub const fn buffer_size(display_size: Size) -> usize {
+        (display_size.width as usize * display_size.height as usize + 7) / 8
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshPolicy {
+    pub full_every: Option<u32>,
+    pub full_after_changed_pixels: Option<usize>,
+}
+
+impl RefreshPolicy {
+    pub const fn new() -> Self {
+        Self {
+            full_every: None,
+            full_after_changed_pixels: None,
+        }
+    }
+}
+
+pub struct Buffered<T, B, R = B, S = ()> {
+    current: B,
+    reference: R,
     target: T,
+    area: Rectangle,
+    spans: S,
+    policy: RefreshPolicy,
+    partial_flushes: u32,
+    changed_pixels: usize,
+    invalid: Option<Rectangle>,
 }
 
 pub const fn buffer_size<C>(display_size: Size) -> usize
@@ -20,7 +146,7 @@ where
     PackedFramebuffer::<C>::buffer_size(display_size)
 }
 
-impl<'a, T> Buffered<'a, T>
+impl<'a, T> Buffered<T, PackedFramebuffer<'a, T::Color>>
 where
     T: DrawTarget,
     T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
@@ -28,36 +154,156 @@ where
     pub(crate) fn new(draw_buf: &'a mut [u8], reference_buf: &'a mut [u8], display: T) -> Self {
         let bbox = display.bounding_box();
 
-        Self {
-            current: PackedFramebuffer::<T::Color>::new(
+        Self::with_framebuffers(
+            PackedFramebuffer::<T::Color>::new(
                 draw_buf,
                 bbox.size.width as _,
                 bbox.size.height as _,
             ),
-            reference: PackedFramebuffer::<T::Color>::new(
+            PackedFramebuffer::<T::Color>::new(
                 reference_buf,
                 bbox.size.width as _,
                 bbox.size.height as _,
             ),
+            display,
+        )
+    }
+}
+
+impl<T, B, R> Buffered<T, B, R>
+where
+    T: DrawTarget,
+    T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    B: Framebuffer<Color = T::Color>,
+    R: ReferenceBuffer<Color = T::Color>,
+{
+    pub(crate) fn with_framebuffers(current: B, reference: R, display: T) -> Self {
+        let area = display.bounding_box();
+
+        Self::with_area(current, reference, display, &area)
+    }
+
+    pub(crate) fn with_area(current: B, reference: R, display: T, area: &Rectangle) -> Self {
+        assert_eq!(
+            current.size(),
+            area.size,
+            "Framebuffer size does not match the buffered area size"
+        );
+
+        Self {
+            current,
+            reference,
             target: display,
+            area: *area,
+            spans: (),
+            policy: RefreshPolicy::new(),
+            partial_flushes: 0,
+            changed_pixels: 0,
+            invalid: None,
+        }
+    }
+}
+
+impl<T, B, R, S> Buffered<T, B, R, S>
+where
+    T: DrawTarget,
+    T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    B: Framebuffer<Color = T::Color>,
+    R: ReferenceBuffer<Color = T::Color>,
+    S: RowSpans,
+{
+    pub fn with_row_spans<S2>(self, spans: S2) -> Buffered<T, B, R, S2>
+    where
+        S2: RowSpans,
+    {
+        Buffered {
+            current: self.current,
+            reference: self.reference,
+            target: self.target,
+            area: self.area,
+            spans,
+            policy: self.policy,
+            partial_flushes: self.partial_flushes,
+            changed_pixels: self.changed_pixels,
+            invalid: self.invalid,
+        }
+    }
+
+    pub fn row_spans(&self) -> &S {
+        &self.spans
+    }
+
+    pub fn invalidate(&mut self) {
+        self.invalid = Some(self.current.bounding_box());
+    }
+
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    pub fn invalidate_area(&mut self, area: &Rectangle) {
+        let area = Rectangle::new(area.top_left - self.area.top_left, area.size)
+            .intersection(&self.current.bounding_box());
+
+        if !area.is_zero_sized() {
+            self.invalid = Some(if let Some(invalid) = self.invalid {
+                union(&invalid, &area)
+            } else {
+                area
+            });
+        }
+    }
+
+    pub fn seed(&mut self, color: T::Color) {
+        let mut current = self.current.packed();
+
+        current.clear(color).unwrap();
+        self.reference.sync(&current);
+
+        self.invalid = None;
+    }
+
+    pub fn refresh_policy(&self) -> &RefreshPolicy {
+        &self.policy
+    }
+
+    pub fn set_refresh_policy(&mut self, policy: RefreshPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn blit(&mut self, sprite: &Sprite<'_, T::Color>, at: Point) -> Result<(), T::Error> {
+        let area = Rectangle::new(at, sprite.size());
+
+        if self.area.intersection(&area) == area && self.spans.is_fully_visible(&area) {
+            self.current.packed().blit(sprite, at - self.area.top_left);
+
+            Ok(())
+        } else {
+            Image::new(sprite, at).draw(self)
         }
     }
 }
 
-impl<'a, T> OriginDimensions for Buffered<'a, T>
+impl<T, B, R, S> OriginDimensions for Buffered<T, B, R, S>
 where
     T: DrawTarget,
     T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    B: Framebuffer<Color = T::Color>,
+    R: ReferenceBuffer<Color = T::Color>,
+    S: RowSpans,
 {
     fn size(&self) -> Size {
-        self.current.size()
+        self.target.bounding_box().size
     }
 }
 
-impl<'a, T> DrawTarget for Buffered<'a, T>
+impl<T, B, R, S> DrawTarget for Buffered<T, B, R, S>
 where
     T: DrawTarget,
     T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    B: Framebuffer<Color = T::Color>,
+    R: ReferenceBuffer<Color = T::Color>,
+    S: RowSpans,
 {
     type Error = T::Error;
 
@@ -67,7 +313,28 @@ where
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        self.current.draw_iter(pixels).unwrap();
+        let area = self.area;
+        let spans = &self.spans;
+        let mut current = self.current.packed();
+
+        let mut pixels = pixels
+            .into_iter()
+            .filter(|pixel| spans.is_visible(pixel.0))
+            .peekable();
+
+        while pixels.peek().is_some() {
+            let inside = from_fn(|| pixels.next_if(|pixel| area.contains(pixel.0)));
+
+            current
+                .draw_iter(inside.map(|pixel| Pixel(pixel.0 - area.top_left, pixel.1)))
+                .unwrap();
+
+            if pixels.peek().is_some() {
+                let outside = from_fn(|| pixels.next_if(|pixel| !area.contains(pixel.0)));
+
+                self.target.draw_iter(outside)?;
+            }
+        }
 
         Ok(())
     }
@@ -76,32 +343,645 @@ where
     where
         I: IntoIterator<Item = Self::Color>,
     {
-        self.current.fill_contiguous(area, colors).unwrap();
+        let inside = area.intersection(&self.area);
 
-        Ok(())
+        if inside == *area {
+            self.current
+                .packed()
+                .translated(-self.area.top_left)
+                .span_clipped(&self.spans)
+                .fill_contiguous(area, colors)
+                .unwrap();
+
+            Ok(())
+        } else if inside.is_zero_sized() {
+            self.target
+                .span_clipped(&self.spans)
+                .fill_contiguous(area, colors)
+        } else {
+            self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            )
+        }
     }
 
     fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
-        self.current.fill_solid(area, color).unwrap();
+        let inside = area.intersection(&self.area);
+
+        if !inside.is_zero_sized() {
+            self.current
+                .packed()
+                .translated(-self.area.top_left)
+                .span_clipped(&self.spans)
+                .fill_solid(&inside, color)
+                .unwrap();
+        }
+
+        let mut target = self.target.span_clipped(&self.spans);
+
+        for outside in surrounding(area, &inside) {
+            target.fill_solid(&outside, color)?;
+        }
 
         Ok(())
     }
 
     fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        self.current.clear(color).unwrap();
+        if self.spans.is_fully_visible(&self.area) {
+            self.current.packed().clear(color).unwrap();
+        } else {
+            self.current
+                .packed()
+                .translated(-self.area.top_left)
+                .span_clipped(&self.spans)
+                .fill_solid(&self.area, color)
+                .unwrap();
+        }
+
+        let bbox = self.target.bounding_box();
+        let mut target = self.target.span_clipped(&self.spans);
+
+        for outside in surrounding(&bbox, &bbox.intersection(&self.area)) {
+            target.fill_solid(&outside, color)?;
+        }
 
         Ok(())
     }
 }
 
-impl<'a, T> Flushable for Buffered<'a, T>
+impl<T, B, R, S> Flushable for Buffered<T, B, R, S>
 where
     T: Flushable,
     T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    B: Framebuffer<Color = T::Color>,
+    R: ReferenceBuffer<Color = T::Color>,
+    S: RowSpans,
 {
     fn flush(&mut self) -> Result<(), Self::Error> {
-        self.reference.apply(&self.current, &mut self.target)?;
+        self.flush_with(Refresh::Partial)
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        let area = self.area;
+
+        self.flush_area(&area, refresh)
+    }
+
+    fn flush_area(&mut self, area: &Rectangle, refresh: Refresh) -> Result<(), Self::Error> {
+        let area = Rectangle::new(area.top_left - self.area.top_left, area.size);
+        let (area, refresh) = self.refresh_area(&area, refresh);
+
+        let current = self.current.packed();
+        let bbox = current.bounding_box();
+
+        let invalid = self
+            .invalid
+            .map(|invalid| invalid.intersection(&area))
+            .filter(|invalid| !invalid.is_zero_sized());
+
+        let mut target = self.target.span_clipped(&self.spans);
+        let mut target = target.translated(self.area.top_left);
+
+        let changes = if refresh == Refresh::Full && area == bbox {
+            target.fill_contiguous(&bbox, current.colors())?;
+            self.reference.sync(&current);
+
+            0
+        } else if refresh == Refresh::Full {
+            // The whole area is sent below, so only the changes outside of it are sent here
+            self.reference
+                .apply_area(&current, &area, &mut target.masked(&Outside(area)))?;
+
+            target.fill_contiguous(&area, current.colors_in(&area))?;
+
+            0
+        } else {
+            let changes = self.reference.apply_area(&current, &area, &mut target)?;
+
+            if let Some(invalid) = invalid {
+                target.fill_contiguous(&invalid, current.colors_in(&invalid))?;
+            }
+
+            changes
+        };
+
+        if self
+            .invalid
+            .map_or(false, |invalid| area.intersection(&invalid) == invalid)
+        {
+            self.invalid = None;
+        }
+
+        self.count_flush(&area, refresh, changes);
+
+        self.target.flush_with(refresh)
+    }
+}
+
+impl<T, B, R, S> Buffered<T, B, R, S>
+where
+    T: Flushable,
+    T::Color: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+    B: Framebuffer<Color = T::Color>,
+    R: ReferenceBuffer<Color = T::Color>,
+    S: RowSpans,
+{
+    const TWO_BPP_CHECK: () = assert!(
+        PackedFramebuffer::<T::Color>::BITS_PER_PIXEL == 2,
+        "Bitplane flushing is only supported for 2bpp colors"
+    );
+
+    pub fn flush_full(&mut self) -> Result<(), T::Error> {
+        self.flush_with(Refresh::Full)
+    }
+
+    #[allow(clippy::let_unit_value)]
+    pub fn flush_planes<F>(
+        &mut self,
+        plane_buf: &mut [u8],
+        refresh: Refresh,
+        mut flusher: F,
+    ) -> Result<(), T::Error>
+    where
+        F: FnMut(&mut T, &Rectangle, Refresh, Bitplane, &[u8]) -> Result<(), T::Error>,
+    {
+        let _ = Self::TWO_BPP_CHECK;
+
+        let plane_len = Bitplane::buffer_size(self.current.size());
+
+        assert!(
+            plane_buf.len() >= plane_len,
+            "Bitplane buffer is smaller than `Bitplane::buffer_size` of the display"
+        );
+
+        let bbox = self.current.bounding_box();
+        let (_, refresh) = self.refresh_area(&bbox, refresh);
+
+        let current = self.current.packed();
+
+        let mut counter = Counter {
+            size: self.target.bounding_box().size,
+            count: 0,
+            _color: PhantomData,
+        };
+
+        self.reference
+            .diff_area(
+                &current,
+                &bbox,
+                &mut counter
+                    .span_clipped(&self.spans)
+                    .translated(self.area.top_left),
+            )
+            .unwrap();
+
+        let changes = counter.count;
+
+        if refresh == Refresh::Partial && changes == 0 && self.invalid.is_none() {
+            return Ok(());
+        }
+
+        let plane_buf = &mut plane_buf[..plane_len];
+
+        for (plane, shift) in [(Bitplane::Msb, 1), (Bitplane::Lsb, 0)] {
+            plane_buf.fill(0);
+
+            for (index, byte) in current.bytes().iter().enumerate() {
+                for pixel in 0..4 {
+                    if (byte >> (pixel * 2 + shift)) & 1 != 0 {
+                        let bit = index * 4 + pixel;
+
+                        plane_buf[bit / 8] |= 0x80 >> (bit % 8);
+                    }
+                }
+            }
+
+            flusher(&mut self.target, &self.area, refresh, plane, plane_buf)?;
+        }
+
+        self.reference.sync(&current);
+        self.invalid = None;
+
+        self.count_flush(&bbox, refresh, changes);
+
+        self.target.flush_with(refresh)
+    }
+
+    fn refresh_area(&self, area: &Rectangle, refresh: Refresh) -> (Rectangle, Refresh) {
+        let bbox = self.current.bounding_box();
+
+        if self.full_refresh_due() {
+            // A full refresh due to the policy covers the whole area, as it resets the counters
+            (bbox, Refresh::Full)
+        } else {
+            (area.intersection(&bbox), refresh)
+        }
+    }
+
+    fn count_flush(&mut self, area: &Rectangle, refresh: Refresh, changes: usize) {
+        if refresh == Refresh::Partial {
+            self.partial_flushes += 1;
+            self.changed_pixels += changes;
+        } else if *area == self.current.bounding_box() {
+            self.partial_flushes = 0;
+            self.changed_pixels = 0;
+        }
+    }
+
+    fn full_refresh_due(&self) -> bool {
+        self.policy
+            .full_every
+            .map_or(false, |every| self.partial_flushes >= every)
+            || self
+                .policy
+                .full_after_changed_pixels
+                .map_or(false, |threshold| self.changed_pixels >= threshold)
+    }
+}
+
+struct Outside(Rectangle);
+
+impl<C> PixelMask<C> for Outside {
+    fn is_drawn(&self, point: Point, _color: C) -> bool {
+        !self.0.contains(point)
+    }
+}
+
+struct Counter<C> {
+    size: Size,
+    count: usize,
+    _color: PhantomData<C>,
+}
+
+impl<C> OriginDimensions for Counter<C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C> DrawTarget for Counter<C>
+where
+    C: PixelColor,
+{
+    type Error = Infallible;
+
+    type Color = C;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.count += pixels.into_iter().count();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::raw::RawU2;
+    use embedded_graphics::prelude::{
+        DrawTarget, OriginDimensions, PixelColor, Point, PointsIter, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{Bitplane, Buffered, ReferenceBuffer, RefreshPolicy};
+    use crate::draw_target::{
+        Flushable, Framebuffer, PackedFramebuffer, Refresh, RowSpans, Viewports,
+    };
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl PixelColor for Gray {
+        type Raw = RawU2;
+    }
+
+    impl From<Gray> for RawU2 {
+        fn from(color: Gray) -> Self {
+            RawU2::new(color.0)
+        }
+    }
+
+    impl From<u8> for Gray {
+        fn from(bits: u8) -> Self {
+            Gray(bits)
+        }
+    }
+
+    #[derive(Default)]
+    struct Display {
+        pixels: [[u8; 16]; 4],
+        drawn: usize,
+        // The refresh of every flush and the number of pixels drawn before it
+        flushes: [Option<(Refresh, usize)>; 8],
+        flush_count: usize,
+    }
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            Size::new(16, 4)
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Error = ();
+
+        type Color = Gray;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.pixels[point.y as usize][point.x as usize] = color.0;
+                self.drawn += 1;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Flushable for Display {
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flush_with(Refresh::Partial)
+        }
+
+        fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+            self.flushes[self.flush_count] = Some((refresh, self.drawn));
+            self.flush_count += 1;
+            self.drawn = 0;
+
+            Ok(())
+        }
+    }
+
+    type Plane = (Rectangle, Refresh, Bitplane, [u8; 2]);
+
+    fn flush_planes<B, R, S>(
+        display: &mut Buffered<Display, B, R, S>,
+        refresh: Refresh,
+        fail: Option<Bitplane>,
+    ) -> (Result<(), ()>, [Option<Plane>; 2])
+    where
+        B: Framebuffer<Color = Gray>,
+        R: ReferenceBuffer<Color = Gray>,
+        S: RowSpans,
+    {
+        let mut plane_buf = [0; 2];
+        let mut planes = [None; 2];
+        let mut count = 0;
+
+        let result =
+            display.flush_planes(&mut plane_buf, refresh, |_, area, refresh, plane, data| {
+                if fail == Some(plane) {
+                    return Err(());
+                }
+
+                planes[count] = Some((*area, refresh, plane, [data[0], data[1]]));
+                count += 1;
+
+                Ok(())
+            });
+
+        (result, planes)
+    }
+
+    #[test]
+    fn flushes_the_planes_of_the_area() {
+        let area = Rectangle::new(Point::new(8, 2), Size::new(8, 2));
+
+        let mut current = [0; 4];
+        let mut reference = [0; 4];
+        let mut display = Buffered::with_area(
+            PackedFramebuffer::<Gray>::new(&mut current, 8, 2),
+            PackedFramebuffer::<Gray>::new(&mut reference, 8, 2),
+            Display::default(),
+            &area,
+        );
+
+        display
+            .draw_iter([
+                Pixel(Point::new(9, 2), Gray(3)),
+                Pixel(Point::new(15, 3), Gray(2)),
+            ])
+            .unwrap();
+
+        // A failing upload keeps the changes for the next flush
+        assert_eq!(
+            flush_planes(&mut display, Refresh::Partial, Some(Bitplane::Lsb)).0,
+            Err(())
+        );
+        assert_eq!(display.target.flush_count, 0);
+
+        assert_eq!(
+            flush_planes(&mut display, Refresh::Partial, None),
+            (
+                Ok(()),
+                [
+                    Some((area, Refresh::Partial, Bitplane::Msb, [0x40, 0x01])),
+                    Some((area, Refresh::Partial, Bitplane::Lsb, [0x40, 0x00])),
+                ]
+            )
+        );
+        assert_eq!(display.target.flushes[..1], [Some((Refresh::Partial, 0))]);
+
+        assert_eq!(
+            flush_planes(&mut display, Refresh::Partial, None),
+            (Ok(()), [None, None])
+        );
+        assert_eq!(display.target.flush_count, 1);
+
+        // The policy forces a full refresh even without changes, and then starts counting again
+        display.set_refresh_policy(RefreshPolicy {
+            full_every: Some(1),
+            ..RefreshPolicy::new()
+        });
+
+        let (result, planes) = flush_planes(&mut display, Refresh::Partial, None);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            planes.map(|plane| plane.map(|plane| plane.1)),
+            [Some(Refresh::Full); 2]
+        );
+        assert_eq!(
+            display.target.flushes[..2],
+            [Some((Refresh::Partial, 0)), Some((Refresh::Full, 0))]
+        );
+
+        assert_eq!(
+            flush_planes(&mut display, Refresh::Partial, None),
+            (Ok(()), [None, None])
+        );
+        assert_eq!(display.target.drawn, 0);
+    }
+
+    #[test]
+    fn flushes_viewports_separately() {
+        let mut current = [0; 16];
+        let mut reference = [0; 16];
+        let mut display = Buffered::new(&mut current, &mut reference, Display::default());
+
+        display.set_refresh_policy(RefreshPolicy {
+            full_every: Some(3),
+            ..RefreshPolicy::new()
+        });
+
+        let mut viewports = Viewports::new(display);
+
+        let [mut left, mut right] = viewports
+            .split([
+                Rectangle::new(Point::new(0, 0), Size::new(8, 4)),
+                Rectangle::new(Point::new(8, 0), Size::new(8, 4)),
+            ])
+            .unwrap();
+
+        let pixel = |x, y| [Pixel(Point::new(x, y), Gray(1))];
+
+        left.draw_iter(pixel(1, 1)).unwrap();
+        right.draw_iter(pixel(2, 2)).unwrap();
+
+        left.flush().unwrap();
+        right.flush().unwrap();
+
+        // A full refresh of one viewport sends its area once, and leaves the counters alone
+        left.draw_iter(pixel(3, 3)).unwrap();
+        left.flush_with(Refresh::Full).unwrap();
+
+        right.flush().unwrap();
+
+        // The third partial flush makes a full refresh due, which covers both viewports
+        right.draw_iter(pixel(4, 0)).unwrap();
+        left.flush().unwrap();
+
+        right.draw_iter(pixel(5, 0)).unwrap();
+        right.flush().unwrap();
+
+        let display = viewports.into_inner();
+
+        assert_eq!(
+            display.target.flushes[..6],
+            [
+                Some((Refresh::Partial, 1)),
+                Some((Refresh::Partial, 1)),
+                Some((Refresh::Full, 32)),
+                Some((Refresh::Partial, 0)),
+                Some((Refresh::Full, 64)),
+                Some((Refresh::Partial, 1)),
+            ]
+        );
+        assert_eq!(display.target.pixels[0][12..14], [1, 1]);
+    }
+
+    fn roi_display<'a>(
+        current: &'a mut [u8; 4],
+        reference: &'a mut [u8; 4],
+    ) -> Buffered<Display, PackedFramebuffer<'a, Gray>> {
+        Buffered::with_area(
+            PackedFramebuffer::new(current, 8, 2),
+            PackedFramebuffer::new(reference, 8, 2),
+            Display::default(),
+            &Rectangle::new(Point::new(4, 1), Size::new(8, 2)),
+        )
+    }
+
+    #[test]
+    fn passes_pixels_outside_of_the_area_through() {
+        let mut current = [0; 4];
+        let mut reference = [0; 4];
+        let mut display = roi_display(&mut current, &mut reference);
+
+        let pixels = [
+            (0, 1, 1),
+            (4, 1, 2),
+            (5, 1, 3),
+            (12, 1, 1),
+            (13, 2, 2),
+            (6, 2, 3),
+            (3, 2, 1),
+            (11, 2, 2),
+            (11, 0, 3),
+        ];
+
+        display
+            .draw_iter(pixels.map(|(x, y, color)| Pixel(Point::new(x, y), Gray(color))))
+            .unwrap();
+
+        let inside = |x, y| (4..12).contains(&x) && (1..3).contains(&y);
+
+        for (x, y, color) in pixels {
+            let expected = if inside(x, y) { 0 } else { color };
+
+            assert_eq!(display.target.pixels[y as usize][x as usize], expected);
+        }
+        assert_eq!(display.target.drawn, 5);
+
+        display.flush().unwrap();
+
+        for (x, y, color) in pixels {
+            assert_eq!(display.target.pixels[y as usize][x as usize], color);
+        }
+        assert_eq!(display.target.flushes[0], Some((Refresh::Partial, 9)));
+    }
+
+    #[test]
+    fn fills_across_the_edge_of_the_area() {
+        let mut current = [0; 4];
+        let mut reference = [0; 4];
+        let mut display = roi_display(&mut current, &mut reference);
+
+        let fill = Rectangle::new(Point::new(2, 0), Size::new(6, 3));
+
+        display
+            .fill_contiguous(&fill, (1..=18).map(|index| Gray(index % 4)))
+            .unwrap();
+
+        // Only the 10 pixels outside of the area reach the display before the flush
+        assert_eq!(display.target.drawn, 10);
+        assert_eq!(display.target.pixels[1][4], 0);
+
+        display.flush().unwrap();
+
+        for (index, point) in fill.points().enumerate() {
+            assert_eq!(
+                display.target.pixels[point.y as usize][point.x as usize],
+                (index as u8 + 1) % 4
+            );
+        }
+    }
+
+    #[test]
+    fn flushes_parts_of_an_offset_area() {
+        let mut current = [0; 4];
+        let mut reference = [0; 4];
+        let mut display = roi_display(&mut current, &mut reference);
+
+        display
+            .draw_iter([
+                Pixel(Point::new(5, 1), Gray(1)),
+                Pixel(Point::new(10, 2), Gray(2)),
+            ])
+            .unwrap();
+
+        // The flushed area is given in display coordinates, and may reach outside of the buffered one
+        display
+            .flush_area(
+                &Rectangle::new(Point::new(0, 0), Size::new(8, 4)),
+                Refresh::Partial,
+            )
+            .unwrap();
+
+        assert_eq!(display.target.pixels[1][5], 1);
+        assert_eq!(display.target.pixels[2][10], 0);
+        assert_eq!(display.target.flushes[0], Some((Refresh::Partial, 1)));
+
+        display.flush().unwrap();
 
-        self.target.flush()
+        assert_eq!(display.target.pixels[2][10], 2);
+        assert_eq!(display.target.flushes[1], Some((Refresh::Partial, 1)));
     }
 }
@@ -0,0 +1,47 @@
+use core::time::Duration;
+
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+impl<F> Clock for F
+where
+    F: Fn() -> Duration,
+{
+    fn now(&self) -> Duration {
+        self()
+    }
+}
+
+#[cfg(feature = "std")]
+pub use std_clock::*;
+
+#[cfg(feature = "std")]
+mod std_clock {
+    use core::time::Duration;
+
+    use std::time::Instant;
+
+    use super::Clock;
+
+    #[derive(Copy, Clone, Debug)]
+    pub struct StdClock(Instant);
+
+    impl StdClock {
+        pub fn new() -> Self {
+            Self(Instant::now())
+        }
+    }
+
+    impl Default for StdClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Clock for StdClock {
+        fn now(&self) -> Duration {
+            self.0.elapsed()
+        }
+    }
+}
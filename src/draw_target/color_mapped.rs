@@ -0,0 +1,351 @@
+use core::cmp::min;
+
+use embedded_graphics::pixelcolor::raw::RawData;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::{Dimensions, DrawTarget, PixelColor, Point, RgbColor};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{identity_mapping, CoordinateMapping, Flushable, Refresh};
+
+pub trait ColorMap<C> {
+    fn map_color(&self, color: C) -> C;
+}
+
+impl<C, F> ColorMap<C> for F
+where
+    F: Fn(C) -> C,
+{
+    fn map_color(&self, color: C) -> C {
+        self(color)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Invert(pub bool);
+
+impl<C> ColorMap<C> for Invert
+where
+    C: PixelColor + From<C::Raw> + Into<C::Raw>,
+    <C::Raw as RawData>::Storage: Into<u32>,
+{
+    fn map_color(&self, color: C) -> C {
+        if self.0 {
+            let raw: C::Raw = color.into();
+
+            C::Raw::from_u32(!raw.into_inner().into()).into()
+        } else {
+            color
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Grayscale(pub bool);
+
+impl<C> ColorMap<C> for Grayscale
+where
+    C: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    fn map_color(&self, color: C) -> C {
+        if self.0 {
+            let rgb: Rgb888 = color.into();
+            let luma = (rgb.r() as u32 * 77 + rgb.g() as u32 * 150 + rgb.b() as u32 * 29) >> 8;
+
+            Rgb888::new(luma as _, luma as _, luma as _).into()
+        } else {
+            color
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Lut(pub [u8; 256]);
+
+impl Lut {
+    pub const fn identity() -> Self {
+        let mut table = [0; 256];
+
+        let mut index = 0;
+        while index < table.len() {
+            table[index] = index as u8;
+            index += 1;
+        }
+
+        Self(table)
+    }
+
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(u8) -> u8,
+    {
+        let mut table = [0; 256];
+
+        for (index, value) in table.iter_mut().enumerate() {
+            *value = f(index as u8);
+        }
+
+        Self(table)
+    }
+
+    pub fn brightness(percent: u16) -> Self {
+        Self::from_fn(|value| min(value as u32 * percent as u32 / 100, u8::MAX as u32) as u8)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn gamma(gamma: f32) -> Self {
+        Self::from_fn(|value| {
+            ((value as f32 / u8::MAX as f32).powf(gamma) * u8::MAX as f32).round() as u8
+        })
+    }
+}
+
+impl Default for Lut {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<C> ColorMap<C> for Lut
+where
+    C: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    fn map_color(&self, color: C) -> C {
+        let rgb: Rgb888 = color.into();
+
+        Rgb888::new(
+            self.0[rgb.r() as usize],
+            self.0[rgb.g() as usize],
+            self.0[rgb.b() as usize],
+        )
+        .into()
+    }
+}
+
+pub struct ColorMapped<'a, T, M>
+where
+    T: DrawTarget,
+{
+    parent: &'a mut T,
+    map: &'a M,
+}
+
+impl<'a, T, M> ColorMapped<'a, T, M>
+where
+    T: DrawTarget,
+    M: ColorMap<T::Color>,
+{
+    pub(crate) fn new(parent: &'a mut T, map: &'a M) -> Self {
+        Self { parent, map }
+    }
+}
+
+impl<'a, T, M> DrawTarget for ColorMapped<'a, T, M>
+where
+    T: DrawTarget,
+    M: ColorMap<T::Color>,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let map = self.map;
+
+        self.parent.draw_iter(
+            pixels
+                .into_iter()
+                .map(|pixel| Pixel(pixel.0, map.map_color(pixel.1))),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let map = self.map;
+
+        self.parent
+            .fill_contiguous(area, colors.into_iter().map(|color| map.map_color(color)))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.parent.fill_solid(area, self.map.map_color(color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.parent.clear(self.map.map_color(color))
+    }
+}
+
+impl<'a, T, M> Dimensions for ColorMapped<'a, T, M>
+where
+    T: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<'a, T, M> CoordinateMapping for ColorMapped<'a, T, M>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+}
+
+impl<'a, T, M> Flushable for ColorMapped<'a, T, M>
+where
+    T: Flushable,
+    M: ColorMap<T::Color>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::raw::RawU16;
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565, Rgb888};
+    use embedded_graphics::prelude::{
+        DrawTarget, IntoStorage, OriginDimensions, Point, RgbColor, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{ColorMap, Grayscale, Invert, Lut};
+    use crate::draw_target::{DrawTargetExt2, OwnedDrawTargetExt};
+
+    #[derive(Default)]
+    struct Display([Rgb565; 2]);
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            Size::new(2, 1)
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Color = Rgb565;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.0[point.x as usize] = color;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn inverts_the_raw_bits() {
+        assert_eq!(Invert(true).map_color(BinaryColor::On), BinaryColor::Off);
+        assert_eq!(Invert(true).map_color(BinaryColor::Off), BinaryColor::On);
+        assert_eq!(Invert(false).map_color(BinaryColor::On), BinaryColor::On);
+
+        let color = Rgb565::from(RawU16::new(0x1234));
+
+        assert_eq!(Invert(true).map_color(color).into_storage(), 0xedcb);
+        assert_eq!(Invert(true).map_color(Rgb565::BLACK), Rgb565::WHITE);
+        assert_eq!(Invert(false).map_color(color), color);
+    }
+
+    #[test]
+    fn converts_to_luma() {
+        assert_eq!(
+            Grayscale(true).map_color(Rgb888::new(255, 0, 0)),
+            Rgb888::new(76, 76, 76)
+        );
+        assert_eq!(
+            Grayscale(true).map_color(Rgb888::new(10, 200, 30)),
+            Rgb888::new(123, 123, 123)
+        );
+        assert_eq!(Grayscale(true).map_color(Rgb888::WHITE), Rgb888::WHITE);
+        assert_eq!(
+            Grayscale(false).map_color(Rgb888::new(10, 200, 30)),
+            Rgb888::new(10, 200, 30)
+        );
+    }
+
+    #[test]
+    fn maps_each_channel_through_the_table() {
+        let color = Rgb888::new(200, 100, 255);
+
+        assert_eq!(
+            Lut::brightness(50).map_color(color),
+            Rgb888::new(100, 50, 127)
+        );
+        assert_eq!(
+            Lut::brightness(200).map_color(color),
+            Rgb888::new(255, 200, 255)
+        );
+        assert_eq!(Lut::brightness(100), Lut::identity());
+        assert_eq!(
+            Lut::from_fn(|value| u8::MAX - value).map_color(color),
+            Rgb888::new(55, 155, 0)
+        );
+    }
+
+    #[test]
+    fn round_trips_through_rgb888() {
+        let lut = Lut::default();
+
+        for raw in 0..=u16::MAX {
+            let color = Rgb565::from(RawU16::new(raw));
+
+            assert_eq!(lut.map_color(color), color);
+        }
+    }
+
+    #[test]
+    fn toggles_the_map_at_runtime() {
+        let mut display = Display::default().owned_inverted();
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::zero(), Size::new(1, 1)),
+                Rgb565::BLACK,
+            )
+            .unwrap();
+
+        display.set_params(Invert(false));
+        display
+            .draw_iter([Pixel(Point::new(1, 0), Rgb565::BLUE)])
+            .unwrap();
+
+        assert_eq!(display.inner().0, [Rgb565::WHITE, Rgb565::BLUE]);
+
+        let mut display = display.into_inner();
+
+        display.grayscale().clear(Rgb565::RED).unwrap();
+        assert_eq!(display.0, [Rgb565::new(9, 19, 9); 2]);
+
+        let lut = Lut::brightness(0);
+
+        display
+            .color_lut(&lut)
+            .draw_iter([Pixel(Point::new(0, 0), Rgb565::RED)])
+            .unwrap();
+        assert_eq!(display.0, [Rgb565::BLACK, Rgb565::new(9, 19, 9)]);
+    }
+}
@@ -0,0 +1,547 @@
+use core::mem::size_of;
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+use std::vec;
+
+use embedded_graphics::pixelcolor::{Rgb555, Rgb565, Rgb888};
+use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{FbDev, FbDevColor, Flushable};
+
+const fn drm_iowr<T>(nr: u32) -> u32 {
+    (3 << 30) | ((size_of::<T>() as u32) << 16) | ((b'd' as u32) << 8) | nr
+}
+
+const DRM_IOCTL_MODE_GETRESOURCES: u32 = drm_iowr::<DrmModeCardRes>(0xa0);
+const DRM_IOCTL_MODE_GETCRTC: u32 = drm_iowr::<DrmModeCrtc>(0xa1);
+const DRM_IOCTL_MODE_SETCRTC: u32 = drm_iowr::<DrmModeCrtc>(0xa2);
+const DRM_IOCTL_MODE_GETENCODER: u32 = drm_iowr::<DrmModeGetEncoder>(0xa6);
+const DRM_IOCTL_MODE_GETCONNECTOR: u32 = drm_iowr::<DrmModeGetConnector>(0xa7);
+const DRM_IOCTL_MODE_ADDFB: u32 = drm_iowr::<DrmModeFbCmd>(0xae);
+const DRM_IOCTL_MODE_RMFB: u32 = drm_iowr::<u32>(0xaf);
+const DRM_IOCTL_MODE_DIRTYFB: u32 = drm_iowr::<DrmModeFbDirtyCmd>(0xb1);
+const DRM_IOCTL_MODE_CREATE_DUMB: u32 = drm_iowr::<DrmModeCreateDumb>(0xb2);
+const DRM_IOCTL_MODE_MAP_DUMB: u32 = drm_iowr::<DrmModeMapDumb>(0xb3);
+const DRM_IOCTL_MODE_DESTROY_DUMB: u32 = drm_iowr::<DrmModeDestroyDumb>(0xb4);
+
+const DRM_MODE_CONNECTED: u32 = 1;
+const DRM_MODE_TYPE_PREFERRED: u32 = 1 << 3;
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeCardRes {
+    fb_id_ptr: u64,
+    crtc_id_ptr: u64,
+    connector_id_ptr: u64,
+    encoder_id_ptr: u64,
+    count_fbs: u32,
+    count_crtcs: u32,
+    count_connectors: u32,
+    count_encoders: u32,
+    min_width: u32,
+    max_width: u32,
+    min_height: u32,
+    max_height: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+struct DrmModeModeInfo {
+    clock: u32,
+    hdisplay: u16,
+    hsync_start: u16,
+    hsync_end: u16,
+    htotal: u16,
+    hskew: u16,
+    vdisplay: u16,
+    vsync_start: u16,
+    vsync_end: u16,
+    vtotal: u16,
+    vscan: u16,
+    vrefresh: u32,
+    flags: u32,
+    type_: u32,
+    name: [u8; 32],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeCrtc {
+    set_connectors_ptr: u64,
+    count_connectors: u32,
+    crtc_id: u32,
+    fb_id: u32,
+    x: u32,
+    y: u32,
+    gamma_size: u32,
+    mode_valid: u32,
+    mode: DrmModeModeInfo,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeGetEncoder {
+    encoder_id: u32,
+    encoder_type: u32,
+    crtc_id: u32,
+    possible_crtcs: u32,
+    possible_clones: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeGetConnector {
+    encoders_ptr: u64,
+    modes_ptr: u64,
+    props_ptr: u64,
+    prop_values_ptr: u64,
+    count_modes: u32,
+    count_props: u32,
+    count_encoders: u32,
+    encoder_id: u32,
+    connector_id: u32,
+    connector_type: u32,
+    connector_type_id: u32,
+    connection: u32,
+    mm_width: u32,
+    mm_height: u32,
+    subpixel: u32,
+    pad: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeFbCmd {
+    fb_id: u32,
+    width: u32,
+    height: u32,
+    pitch: u32,
+    bpp: u32,
+    depth: u32,
+    handle: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeFbDirtyCmd {
+    fb_id: u32,
+    flags: u32,
+    color: u32,
+    num_clips: u32,
+    clips_ptr: u64,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeCreateDumb {
+    height: u32,
+    width: u32,
+    bpp: u32,
+    flags: u32,
+    handle: u32,
+    pitch: u32,
+    size: u64,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeMapDumb {
+    handle: u32,
+    pad: u32,
+    offset: u64,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct DrmModeDestroyDumb {
+    handle: u32,
+}
+
+pub trait DrmColor: FbDevColor {
+    const DEPTH: u32;
+}
+
+impl DrmColor for Rgb555 {
+    const DEPTH: u32 = 15;
+}
+
+impl DrmColor for Rgb565 {
+    const DEPTH: u32 = 16;
+}
+
+impl DrmColor for Rgb888 {
+    const DEPTH: u32 = 24;
+}
+
+pub struct DrmDumb<C>
+where
+    C: DrmColor,
+    C::Storage: Into<u32>,
+{
+    fb: FbDev<C>,
+    connector_id: u32,
+    saved_crtc: DrmModeCrtc,
+    fb_id: u32,
+    handle: u32,
+}
+
+impl<C> DrmDumb<C>
+where
+    C: DrmColor,
+    C::Storage: Into<u32>,
+{
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let (connector_id, crtc_id, mode) = Self::find_output(&file)?;
+
+        let mut saved_crtc = DrmModeCrtc {
+            crtc_id,
+            ..Default::default()
+        };
+
+        Self::ioctl(&file, DRM_IOCTL_MODE_GETCRTC, &mut saved_crtc)?;
+
+        let mut dumb = DrmModeCreateDumb {
+            width: mode.hdisplay as _,
+            height: mode.vdisplay as _,
+            bpp: size_of::<C::Storage>() as u32 * 8,
+            ..Default::default()
+        };
+
+        Self::ioctl(&file, DRM_IOCTL_MODE_CREATE_DUMB, &mut dumb)?;
+
+        let handle = dumb.handle;
+
+        let mut fb_cmd = DrmModeFbCmd {
+            width: dumb.width,
+            height: dumb.height,
+            pitch: dumb.pitch,
+            bpp: dumb.bpp,
+            depth: C::DEPTH,
+            handle,
+            ..Default::default()
+        };
+
+        if let Err(err) = Self::ioctl(&file, DRM_IOCTL_MODE_ADDFB, &mut fb_cmd) {
+            Self::destroy_dumb(&file, handle);
+
+            return Err(err);
+        }
+
+        let fb_id = fb_cmd.fb_id;
+
+        let mapped = file.try_clone().and_then(|file| {
+            let fb = Self::map_dumb(file, &dumb)?;
+
+            let mut connector_id = connector_id;
+
+            let mut crtc = DrmModeCrtc {
+                set_connectors_ptr: &mut connector_id as *mut u32 as _,
+                count_connectors: 1,
+                crtc_id,
+                fb_id,
+                mode_valid: 1,
+                mode,
+                ..Default::default()
+            };
+
+            Self::ioctl(fb.file(), DRM_IOCTL_MODE_SETCRTC, &mut crtc)?;
+
+            Ok(fb)
+        });
+
+        match mapped {
+            Ok(fb) => Ok(Self {
+                fb,
+                connector_id,
+                saved_crtc,
+                fb_id,
+                handle,
+            }),
+            Err(err) => {
+                Self::remove_fb(&file, fb_id);
+                Self::destroy_dumb(&file, handle);
+
+                Err(err)
+            }
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        self.fb.as_bytes()
+    }
+
+    fn find_output(file: &File) -> io::Result<(u32, u32, DrmModeModeInfo)> {
+        let mut res = DrmModeCardRes::default();
+
+        Self::ioctl(file, DRM_IOCTL_MODE_GETRESOURCES, &mut res)?;
+
+        let mut crtcs = vec![0_u32; res.count_crtcs as _];
+        let mut connectors = vec![0_u32; res.count_connectors as _];
+        let mut encoders = vec![0_u32; res.count_encoders as _];
+
+        res = DrmModeCardRes {
+            crtc_id_ptr: crtcs.as_mut_ptr() as _,
+            connector_id_ptr: connectors.as_mut_ptr() as _,
+            encoder_id_ptr: encoders.as_mut_ptr() as _,
+            count_crtcs: crtcs.len() as _,
+            count_connectors: connectors.len() as _,
+            count_encoders: encoders.len() as _,
+            ..Default::default()
+        };
+
+        Self::ioctl(file, DRM_IOCTL_MODE_GETRESOURCES, &mut res)?;
+
+        crtcs.truncate(res.count_crtcs as _);
+        connectors.truncate(res.count_connectors as _);
+
+        for connector_id in connectors {
+            let mut connector = DrmModeGetConnector {
+                connector_id,
+                ..Default::default()
+            };
+
+            Self::ioctl(file, DRM_IOCTL_MODE_GETCONNECTOR, &mut connector)?;
+
+            if connector.connection != DRM_MODE_CONNECTED || connector.count_modes == 0 {
+                continue;
+            }
+
+            let mut modes = vec![DrmModeModeInfo::default(); connector.count_modes as _];
+            let mut encoders = vec![0_u32; connector.count_encoders as _];
+
+            connector = DrmModeGetConnector {
+                modes_ptr: modes.as_mut_ptr() as _,
+                encoders_ptr: encoders.as_mut_ptr() as _,
+                count_modes: modes.len() as _,
+                count_encoders: encoders.len() as _,
+                connector_id,
+                ..Default::default()
+            };
+
+            Self::ioctl(file, DRM_IOCTL_MODE_GETCONNECTOR, &mut connector)?;
+
+            modes.truncate(connector.count_modes as _);
+            encoders.truncate(connector.count_encoders as _);
+
+            let Some(mode) = modes
+                .iter()
+                .find(|mode| mode.type_ & DRM_MODE_TYPE_PREFERRED != 0)
+                .or_else(|| modes.first())
+            else {
+                continue;
+            };
+
+            // Prefer the CRTC the connector is already driven by
+            let current = core::iter::once(connector.encoder_id).filter(|id| *id != 0);
+
+            for encoder_id in current.chain(encoders) {
+                let mut encoder = DrmModeGetEncoder {
+                    encoder_id,
+                    ..Default::default()
+                };
+
+                Self::ioctl(file, DRM_IOCTL_MODE_GETENCODER, &mut encoder)?;
+
+                let crtc_id = if encoder.crtc_id != 0 {
+                    Some(encoder.crtc_id)
+                } else {
+                    crtcs
+                        .iter()
+                        .enumerate()
+                        .find(|(index, _)| encoder.possible_crtcs & (1 << index) != 0)
+                        .map(|(_, crtc_id)| *crtc_id)
+                };
+
+                if let Some(crtc_id) = crtc_id {
+                    return Ok((connector_id, crtc_id, *mode));
+                }
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "No connected DRM output with a usable CRTC",
+        ))
+    }
+
+    fn map_dumb(file: File, dumb: &DrmModeCreateDumb) -> io::Result<FbDev<C>> {
+        let mut map = DrmModeMapDumb {
+            handle: dumb.handle,
+            ..Default::default()
+        };
+
+        Self::ioctl(&file, DRM_IOCTL_MODE_MAP_DUMB, &mut map)?;
+
+        FbDev::map(
+            file,
+            map.offset as _,
+            dumb.size as _,
+            0,
+            dumb.pitch as _,
+            size_of::<C::Storage>(),
+            Size::new(dumb.width, dumb.height),
+            false,
+        )
+    }
+
+    fn remove_fb(file: &File, mut fb_id: u32) {
+        if let Err(err) = Self::ioctl(file, DRM_IOCTL_MODE_RMFB, &mut fb_id) {
+            warn!(
+                "Removing the DRM framebuffer failed (errno {})",
+                err.raw_os_error().unwrap_or(0)
+            );
+        }
+    }
+
+    fn destroy_dumb(file: &File, handle: u32) {
+        let mut destroy = DrmModeDestroyDumb { handle };
+
+        if let Err(err) = Self::ioctl(file, DRM_IOCTL_MODE_DESTROY_DUMB, &mut destroy) {
+            warn!(
+                "Destroying the DRM dumb buffer failed (errno {})",
+                err.raw_os_error().unwrap_or(0)
+            );
+        }
+    }
+
+    fn ioctl<T>(file: &File, request: u32, data: &mut T) -> io::Result<()> {
+        FbDev::<C>::ioctl(file, request, data)
+    }
+}
+
+impl<C> Drop for DrmDumb<C>
+where
+    C: DrmColor,
+    C::Storage: Into<u32>,
+{
+    fn drop(&mut self) {
+        let file = self.fb.file();
+
+        // Give the CRTC back to whoever drove it before, i.e. the console
+        if self.saved_crtc.fb_id != 0 {
+            let mut connector_id = self.connector_id;
+
+            let mut crtc = DrmModeCrtc {
+                set_connectors_ptr: &mut connector_id as *mut u32 as _,
+                count_connectors: 1,
+                ..core::mem::take(&mut self.saved_crtc)
+            };
+
+            if let Err(err) = Self::ioctl(file, DRM_IOCTL_MODE_SETCRTC, &mut crtc) {
+                warn!(
+                    "Restoring the DRM CRTC failed (errno {})",
+                    err.raw_os_error().unwrap_or(0)
+                );
+            }
+        }
+
+        Self::remove_fb(file, self.fb_id);
+        Self::destroy_dumb(file, self.handle);
+    }
+}
+
+impl<C> OriginDimensions for DrmDumb<C>
+where
+    C: DrmColor,
+    C::Storage: Into<u32>,
+{
+    fn size(&self) -> Size {
+        self.fb.size()
+    }
+}
+
+impl<C> DrawTarget for DrmDumb<C>
+where
+    C: DrmColor,
+    C::Storage: Into<u32>,
+{
+    type Error = io::Error;
+
+    type Color = C;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.fb.draw_iter(pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.fb.fill_solid(area, color)
+    }
+}
+
+impl<C> Flushable for DrmDumb<C>
+where
+    C: DrmColor,
+    C::Storage: Into<u32>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        let mut dirty = DrmModeFbDirtyCmd {
+            fb_id: self.fb_id,
+            ..Default::default()
+        };
+
+        // Only drivers which do not scan out of the dumb buffer directly need to be told
+        match Self::ioctl(self.fb.file(), DRM_IOCTL_MODE_DIRTYFB, &mut dirty) {
+            Err(err) if err.raw_os_error() != Some(libc::ENOSYS) => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{format, fs};
+
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    use super::*;
+
+    #[test]
+    fn ioctl_numbers() {
+        assert_eq!(DRM_IOCTL_MODE_GETRESOURCES, 0xc040_64a0);
+        assert_eq!(DRM_IOCTL_MODE_GETCRTC, 0xc068_64a1);
+        assert_eq!(DRM_IOCTL_MODE_SETCRTC, 0xc068_64a2);
+        assert_eq!(DRM_IOCTL_MODE_GETENCODER, 0xc014_64a6);
+        assert_eq!(DRM_IOCTL_MODE_GETCONNECTOR, 0xc050_64a7);
+        assert_eq!(DRM_IOCTL_MODE_ADDFB, 0xc01c_64ae);
+        assert_eq!(DRM_IOCTL_MODE_RMFB, 0xc004_64af);
+        assert_eq!(DRM_IOCTL_MODE_DIRTYFB, 0xc018_64b1);
+        assert_eq!(DRM_IOCTL_MODE_CREATE_DUMB, 0xc020_64b2);
+        assert_eq!(DRM_IOCTL_MODE_MAP_DUMB, 0xc010_64b3);
+        assert_eq!(DRM_IOCTL_MODE_DESTROY_DUMB, 0xc004_64b4);
+    }
+
+    #[test]
+    fn open_non_drm_device() {
+        let path = std::env::temp_dir().join(format!("gfx-xtra-drm-{}.raw", std::process::id()));
+
+        fs::write(&path, [0; 16]).unwrap();
+
+        let result = DrmDumb::<Rgb565>::open(&path);
+
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            result.err().and_then(|err| err.raw_os_error()),
+            Some(libc::ENOTTY)
+        );
+    }
+}
@@ -1,13 +1,20 @@
-use core::cmp::{max, min};
 use core::convert::Infallible;
 use core::marker::PhantomData;
 
+use embedded_graphics::image::GetPixel;
 use embedded_graphics::prelude::{
-    Dimensions, DrawTarget, IntoStorage, OriginDimensions, PixelColor, Point, RawData, Size,
+    Dimensions, DrawTarget, IntoStorage, OriginDimensions, PixelColor, Point, PointsIter, RawData,
+    Size,
 };
 use embedded_graphics::primitives::Rectangle;
 use embedded_graphics::Pixel;
 
+pub trait Framebuffer: OriginDimensions {
+    type Color;
+
+    fn packed(&mut self) -> PackedFramebuffer<'_, Self::Color>;
+}
+
 pub struct PackedFramebuffer<'a, COLOR> {
     buf: &'a mut [u8],
     width: usize,
@@ -19,9 +26,9 @@ impl<'a, COLOR> PackedFramebuffer<'a, COLOR>
 where
     COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
 {
-    const BITS_PER_PIXEL: usize = Self::bits_per_pixel();
+    pub(crate) const BITS_PER_PIXEL: usize = Self::bits_per_pixel();
     const PIXEL_MASK: u8 = ((1 << Self::BITS_PER_PIXEL) - 1) as u8;
-    const PIXELS_PER_BYTE: usize = 8 / Self::BITS_PER_PIXEL;
+    pub(crate) const PIXELS_PER_BYTE: usize = 8 / Self::BITS_PER_PIXEL;
     const PIXELS_PER_BYTE_SHIFT: usize = if Self::BITS_PER_PIXEL == 8 {
         0
     } else {
@@ -37,6 +44,47 @@ where
         }
     }
 
+    pub fn into_bytes(self) -> &'a mut [u8] {
+        self.buf
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.buf[..self.y_offset(self.height)]
+    }
+
+    pub(crate) fn bytes_mut(&mut self) -> &mut [u8] {
+        let len = self.y_offset(self.height);
+
+        &mut self.buf[..len]
+    }
+
+    pub(crate) fn colors(&self) -> impl Iterator<Item = COLOR> + '_ {
+        self.bytes().iter().flat_map(|byte| {
+            (0..Self::PIXELS_PER_BYTE).map(move |index| Self::pixel_in_byte(*byte, index))
+        })
+    }
+
+    pub(crate) fn colors_in(&self, area: &Rectangle) -> impl Iterator<Item = COLOR> + '_ {
+        self.offsets(*area)
+            .map(|(byte_offset, bits_offset)| self.get(byte_offset, bits_offset))
+    }
+
+    pub(crate) fn row_bytes(&self, y: usize) -> &[u8] {
+        let start = self.y_offset(y);
+
+        &self.buf[start..start + self.bytes_per_row()]
+    }
+
+    pub(crate) fn pixel_in_byte(byte: u8, index: usize) -> COLOR {
+        Self::from_bits((byte >> (Self::PIXELS_PER_BYTE_SHIFT * index)) & Self::PIXEL_MASK)
+    }
+
+    pub(crate) fn copy_rows_from(&mut self, other: &Self, area: &Rectangle) {
+        let stride = self.bytes_per_row();
+
+        copy_rows(self.buf, other.buf, stride, self.size(), area);
+    }
+
     pub const fn buffer_size(display_size: Size) -> usize {
         display_size.width as usize * display_size.height as usize / (8 / Self::bits_per_pixel())
     }
@@ -57,59 +105,91 @@ where
     where
         D: DrawTarget<Color = COLOR>,
     {
-        let width = self.width();
-        let height = self.height();
+        let area = self.bounding_box();
+
+        self.apply_area(new, &area, to)
+    }
+
+    pub(crate) fn apply_area<D>(
+        &mut self,
+        new: &Self,
+        area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = COLOR>,
+    {
+        self.compare_area(new, area, to, true)
+    }
+
+    pub(crate) fn diff_area<D>(
+        &mut self,
+        new: &Self,
+        area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = COLOR>,
+    {
+        self.compare_area(new, area, to, false)
+    }
+
+    fn compare_area<D>(
+        &mut self,
+        new: &Self,
+        area: &Rectangle,
+        to: &mut D,
+        update: bool,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = COLOR>,
+    {
+        let area = area.intersection(&self.bounding_box());
 
         let mut changes = 0_usize;
 
-        let pixels = (0..height)
-            .flat_map(|y| (0..width).map(move |x| (x, y)))
-            .filter_map(|(x, y)| {
-                let bytes_offset = self.y_offset(y as usize) + Self::x_offset(x as usize);
-                let bits_offset = Self::x_bits_offset(x as usize);
+        let pixels = area.points().filter_map(|point| {
+            let (x, y) = (point.x as usize, point.y as usize);
+
+            let bytes_offset = self.y_offset(y) + Self::x_offset(x);
+            let bits_offset = Self::x_bits_offset(x);
 
-                let color = new.get(bytes_offset, bits_offset);
-                if self.get(bytes_offset, bits_offset) != color {
+            let color = new.get(bytes_offset, bits_offset);
+            if self.get(bytes_offset, bits_offset) != color {
+                if update {
                     self.set(bytes_offset, bits_offset, color);
+                }
 
-                    changes += 1;
+                changes += 1;
 
-                    Some(Pixel(Point::new(x as _, y as _), color))
-                } else {
-                    None
-                }
-            });
+                Some(Pixel(point, color))
+            } else {
+                None
+            }
+        });
 
         to.draw_iter(pixels)?;
 
-        #[cfg(feature = "log")]
-        ::log::trace!(
+        trace!(
             "Display updated ({}/{} changed pixels)",
             changes,
-            width * height
+            area.size.width * area.size.height
         );
 
         Ok(changes)
     }
 
     fn offsets(&self, area: Rectangle) -> impl Iterator<Item = (usize, usize)> {
-        let dimensions = self.bounding_box();
-        let bottom_right = dimensions.bottom_right().unwrap_or(dimensions.top_left);
+        let area = area.intersection(&self.bounding_box());
 
-        let x = min(max(area.top_left.x, 0), bottom_right.x) as usize;
-        let y = min(max(area.top_left.y, 0), bottom_right.y) as usize;
+        let x = area.top_left.x as usize;
+        let y = area.top_left.y as usize;
 
-        let xend = min(
-            max(area.top_left.x + area.size.width as i32, 0),
-            bottom_right.x,
-        ) as usize;
-        let yend = min(
-            max(area.top_left.y + area.size.height as i32, 0),
-            bottom_right.y,
-        ) as usize;
+        let xend = x + area.size.width as usize;
+        let yend = y + area.size.height as usize;
 
         (self.y_offset(y)..self.y_offset(yend))
-            .step_by(self.bytes_per_row())
+            .step_by(self.bytes_per_row().max(1))
             .flat_map(move |y_offset| {
                 (x..xend).map(move |x| (y_offset + Self::x_offset(x), Self::x_bits_offset(x)))
             })
@@ -151,7 +231,7 @@ where
     }
 
     #[inline(always)]
-    fn bytes_per_row(&self) -> usize {
+    pub(crate) fn bytes_per_row(&self) -> usize {
         self.width() / Self::PIXELS_PER_BYTE
     }
 
@@ -168,6 +248,22 @@ where
     }
 }
 
+impl<'a, COLOR> Framebuffer for PackedFramebuffer<'a, COLOR>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Color = COLOR;
+
+    fn packed(&mut self) -> PackedFramebuffer<'_, Self::Color> {
+        PackedFramebuffer {
+            buf: self.buf,
+            width: self.width,
+            height: self.height,
+            _color: PhantomData,
+        }
+    }
+}
+
 impl<'a, COLOR> OriginDimensions for PackedFramebuffer<'a, COLOR>
 where
     COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
@@ -177,6 +273,22 @@ where
     }
 }
 
+impl<'a, COLOR> GetPixel for PackedFramebuffer<'a, COLOR>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Color = COLOR;
+
+    fn pixel(&self, point: Point) -> Option<Self::Color> {
+        self.bounding_box().contains(point).then(|| {
+            self.get(
+                self.y_offset(point.y as usize) + Self::x_offset(point.x as usize),
+                Self::x_bits_offset(point.x as usize),
+            )
+        })
+    }
+}
+
 impl<'a, COLOR> DrawTarget for PackedFramebuffer<'a, COLOR>
 where
     COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
@@ -210,6 +322,20 @@ where
     where
         I: IntoIterator<Item = Self::Color>,
     {
+        let visible = area.intersection(&self.bounding_box());
+
+        if visible.is_zero_sized() {
+            trace!("Filling an area outside of the framebuffer: {:?}", area);
+
+            return Ok(());
+        } else if visible != *area {
+            return self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            );
+        }
+
         let mut colors = colors.into_iter();
 
         for (byte_offset, bits_offset) in self.offsets(*area) {
@@ -222,6 +348,12 @@ where
     }
 
     fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if area.intersection(&self.bounding_box()).is_zero_sized() {
+            trace!("Filling an area outside of the framebuffer: {:?}", area);
+
+            return Ok(());
+        }
+
         for (byte_offset, bits_offset) in self.offsets(*area) {
             self.set(byte_offset, bits_offset, color);
         }
@@ -243,3 +375,170 @@ where
         Ok(())
     }
 }
+
+pub(crate) fn copy_rows(dst: &mut [u8], src: &[u8], stride: usize, size: Size, area: &Rectangle) {
+    let area = area.intersection(&Rectangle::new(Point::zero(), size));
+
+    if area.is_zero_sized() {
+        return;
+    }
+
+    let start = area.top_left.y as usize * stride;
+    let end = start + area.size.height as usize * stride;
+
+    dst[start..end].copy_from_slice(&src[start..end]);
+}
+
+// `N` is always `buffer_size::<COLOR>(W x H)`, but stable Rust can't use generic parameters in
+// array lengths, so it has to be spelled out by the user and is checked by `SIZE_CHECK` instead
+pub struct StaticFramebuffer<COLOR, const W: usize, const H: usize, const N: usize> {
+    buf: [u8; N],
+    _color: PhantomData<COLOR>,
+}
+
+impl<COLOR, const W: usize, const H: usize, const N: usize> StaticFramebuffer<COLOR, W, H, N>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    const SIZE_CHECK: () = assert!(
+        N == PackedFramebuffer::<COLOR>::buffer_size(Size::new(W as _, H as _)),
+        "Buffer size `N` does not match the framebuffer dimensions and color depth; compute it with `gfx_xtra::draw_target::buffer_size::<C>(Size::new(W, H))` or `PackedFramebuffer::<C>::buffer_size`"
+    );
+
+    #[allow(clippy::let_unit_value)]
+    pub const fn new() -> Self {
+        let _ = Self::SIZE_CHECK;
+
+        Self {
+            buf: [0; N],
+            _color: PhantomData,
+        }
+    }
+}
+
+impl<COLOR, const W: usize, const H: usize, const N: usize> StaticFramebuffer<COLOR, W, H, N> {
+    pub(crate) fn copy_rows_from(&mut self, other: &Self, area: &Rectangle) {
+        copy_rows(
+            &mut self.buf,
+            &other.buf,
+            N / H.max(1),
+            Size::new(W as _, H as _),
+            area,
+        );
+    }
+}
+
+impl<COLOR, const W: usize, const H: usize, const N: usize> Default
+    for StaticFramebuffer<COLOR, W, H, N>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<COLOR, const W: usize, const H: usize, const N: usize> Framebuffer
+    for StaticFramebuffer<COLOR, W, H, N>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Color = COLOR;
+
+    fn packed(&mut self) -> PackedFramebuffer<'_, Self::Color> {
+        PackedFramebuffer {
+            buf: &mut self.buf,
+            width: W,
+            height: H,
+            _color: PhantomData,
+        }
+    }
+}
+
+impl<COLOR, const W: usize, const H: usize, const N: usize> OriginDimensions
+    for StaticFramebuffer<COLOR, W, H, N>
+{
+    fn size(&self) -> Size {
+        Size::new(W as _, H as _)
+    }
+}
+
+impl<COLOR, const W: usize, const H: usize, const N: usize> DrawTarget
+    for StaticFramebuffer<COLOR, W, H, N>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Error = Infallible;
+
+    type Color = COLOR;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.packed().draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.packed().fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.packed().fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.packed().clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::image::GetPixel;
+    use embedded_graphics::pixelcolor::raw::RawU2;
+    use embedded_graphics::prelude::{Dimensions, DrawTarget, PixelColor, Point, PointsIter, Size};
+    use embedded_graphics::primitives::Rectangle;
+
+    use super::PackedFramebuffer;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl PixelColor for Gray {
+        type Raw = RawU2;
+    }
+
+    impl From<Gray> for RawU2 {
+        fn from(color: Gray) -> Self {
+            RawU2::new(color.0)
+        }
+    }
+
+    impl From<u8> for Gray {
+        fn from(bits: u8) -> Self {
+            Gray(bits)
+        }
+    }
+
+    #[test]
+    fn fills_the_last_row_and_column() {
+        let mut buf = [0; 6];
+        let mut fb = PackedFramebuffer::<Gray>::new(&mut buf, 8, 3);
+
+        fb.fill_solid(&Rectangle::new(Point::new(6, 1), Size::new(4, 4)), Gray(3))
+            .unwrap();
+
+        for point in fb.bounding_box().points() {
+            let expected = if point.x >= 6 && point.y >= 1 { 3 } else { 0 };
+
+            assert_eq!(fb.pixel(point), Some(Gray(expected)), "{point:?}");
+        }
+
+        fb.clear(Gray(2)).unwrap();
+
+        assert_eq!(fb.pixel(Point::new(7, 2)), Some(Gray(2)));
+    }
+}
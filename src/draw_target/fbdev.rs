@@ -0,0 +1,394 @@
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::slice;
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use embedded_graphics::pixelcolor::raw::RawData;
+use embedded_graphics::pixelcolor::{Bgr555, Bgr565, Bgr888, Gray8, Rgb555, Rgb565, Rgb888};
+use embedded_graphics::prelude::{
+    Dimensions, DrawTarget, IntoStorage, OriginDimensions, PixelColor, Size,
+};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::Flushable;
+
+const FBIOGET_VSCREENINFO: u32 = 0x4600;
+const FBIOGET_FSCREENINFO: u32 = 0x4602;
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct FbBitfield {
+    offset: u32,
+    length: u32,
+    msb_right: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct FbVarScreenInfo {
+    xres: u32,
+    yres: u32,
+    xres_virtual: u32,
+    yres_virtual: u32,
+    xoffset: u32,
+    yoffset: u32,
+    bits_per_pixel: u32,
+    grayscale: u32,
+    red: FbBitfield,
+    green: FbBitfield,
+    blue: FbBitfield,
+    transp: FbBitfield,
+    nonstd: u32,
+    activate: u32,
+    height: u32,
+    width: u32,
+    accel_flags: u32,
+    pixclock: u32,
+    left_margin: u32,
+    right_margin: u32,
+    upper_margin: u32,
+    lower_margin: u32,
+    hsync_len: u32,
+    vsync_len: u32,
+    sync: u32,
+    vmode: u32,
+    rotate: u32,
+    colorspace: u32,
+    reserved: [u32; 4],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct FbFixScreenInfo {
+    id: [u8; 16],
+    smem_start: libc::c_ulong,
+    smem_len: u32,
+    type_: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    line_length: u32,
+    mmio_start: libc::c_ulong,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+}
+
+pub trait FbDevColor: PixelColor + IntoStorage {
+    const RED: (u32, u32);
+    const GREEN: (u32, u32);
+    const BLUE: (u32, u32);
+}
+
+macro_rules! fbdev_color {
+    ($color:ty, $red:expr, $green:expr, $blue:expr) => {
+        impl FbDevColor for $color {
+            const RED: (u32, u32) = $red;
+            const GREEN: (u32, u32) = $green;
+            const BLUE: (u32, u32) = $blue;
+        }
+    };
+}
+
+fbdev_color!(Rgb555, (10, 5), (5, 5), (0, 5));
+fbdev_color!(Bgr555, (0, 5), (5, 5), (10, 5));
+fbdev_color!(Rgb565, (11, 5), (5, 6), (0, 5));
+fbdev_color!(Bgr565, (0, 5), (5, 6), (11, 5));
+fbdev_color!(Rgb888, (16, 8), (8, 8), (0, 8));
+fbdev_color!(Bgr888, (0, 8), (8, 8), (16, 8));
+fbdev_color!(Gray8, (0, 8), (0, 8), (0, 8));
+
+impl FbBitfield {
+    fn matches(&self, (offset, length): (u32, u32)) -> bool {
+        self.offset == offset && self.length == length && self.msb_right == 0
+    }
+}
+
+pub struct FbDev<C> {
+    file: File,
+    mem: *mut u8,
+    mem_len: usize,
+    offset: usize,
+    line_length: usize,
+    bytes_per_pixel: usize,
+    size: Size,
+    sync: bool,
+    _color: PhantomData<C>,
+}
+
+impl<C> FbDev<C>
+where
+    C: FbDevColor,
+    C::Storage: Into<u32>,
+{
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let mut var = FbVarScreenInfo::default();
+        let mut fix = FbFixScreenInfo::default();
+
+        Self::ioctl(&file, FBIOGET_VSCREENINFO, &mut var)?;
+        Self::ioctl(&file, FBIOGET_FSCREENINFO, &mut fix)?;
+
+        let min_bits_per_pixel = (C::Raw::BITS_PER_PIXEL as u32 + 7) / 8 * 8;
+        let max_bits_per_pixel = size_of::<C::Storage>() as u32 * 8;
+
+        if var.bits_per_pixel % 8 != 0
+            || var.bits_per_pixel < min_bits_per_pixel
+            || var.bits_per_pixel > max_bits_per_pixel
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Framebuffer bits per pixel do not match the color",
+            ));
+        }
+
+        if !var.red.matches(C::RED) || !var.green.matches(C::GREEN) || !var.blue.matches(C::BLUE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Framebuffer color channels do not match the color",
+            ));
+        }
+
+        let bytes_per_pixel = var.bits_per_pixel as usize / 8;
+
+        Self::map(
+            file,
+            0,
+            fix.smem_len as _,
+            var.yoffset as usize * fix.line_length as usize
+                + var.xoffset as usize * bytes_per_pixel,
+            fix.line_length as _,
+            bytes_per_pixel,
+            Size::new(var.xres, var.yres),
+            false,
+        )
+    }
+
+    pub fn open_file(path: impl AsRef<Path>, size: Size) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let bytes_per_pixel = size_of::<C::Storage>();
+        let line_length = size.width as usize * bytes_per_pixel;
+        let mem_len = line_length * size.height as usize;
+
+        file.set_len(mem_len as _)?;
+
+        Self::map(
+            file,
+            0,
+            mem_len,
+            0,
+            line_length,
+            bytes_per_pixel,
+            size,
+            true,
+        )
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.mem, self.mem_len) }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn map(
+        file: File,
+        mem_offset: libc::off_t,
+        mem_len: usize,
+        offset: usize,
+        line_length: usize,
+        bytes_per_pixel: usize,
+        size: Size,
+        sync: bool,
+    ) -> io::Result<Self> {
+        if offset + line_length * size.height as usize > mem_len
+            || size.width as usize * bytes_per_pixel > line_length
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Framebuffer memory is smaller than the screen",
+            ));
+        }
+
+        let mem = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                mem_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                mem_offset,
+            )
+        };
+
+        if mem == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            file,
+            mem: mem as *mut u8,
+            mem_len,
+            offset,
+            line_length,
+            bytes_per_pixel,
+            size,
+            sync,
+            _color: PhantomData,
+        })
+    }
+
+    pub(super) fn file(&self) -> &File {
+        &self.file
+    }
+
+    pub(super) fn ioctl<T>(file: &File, request: u32, data: &mut T) -> io::Result<()> {
+        if unsafe { libc::ioctl(file.as_raw_fd(), request as _, data as *mut T) } < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn mem(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.mem, self.mem_len) }
+    }
+
+    fn set(&mut self, x: usize, y: usize, color: C) {
+        let bytes_per_pixel = self.bytes_per_pixel;
+        let offset = self.offset + y * self.line_length + x * bytes_per_pixel;
+
+        let value: u32 = color.into_storage().into();
+
+        self.mem()[offset..offset + bytes_per_pixel]
+            .copy_from_slice(&value.to_le_bytes()[..bytes_per_pixel]);
+    }
+}
+
+impl<C> Drop for FbDev<C> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.mem as *mut _, self.mem_len);
+        }
+    }
+}
+
+unsafe impl<C> Send for FbDev<C> where C: Send {}
+
+impl<C> OriginDimensions for FbDev<C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C> DrawTarget for FbDev<C>
+where
+    C: FbDevColor,
+    C::Storage: Into<u32>,
+{
+    type Error = io::Error;
+
+    type Color = C;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bbox = self.bounding_box();
+
+        for pixel in pixels {
+            if bbox.contains(pixel.0) {
+                self.set(pixel.0.x as _, pixel.0.y as _, pixel.1);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+
+        for y in area.rows() {
+            for x in area.columns() {
+                self.set(x as _, y as _, color);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> Flushable for FbDev<C>
+where
+    C: FbDevColor,
+    C::Storage: Into<u32>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        if self.sync && unsafe { libc::msync(self.mem as *mut _, self.mem_len, libc::MS_SYNC) } < 0
+        {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{format, fs, vec};
+
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{DrawTarget, Point, RgbColor, Size};
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{FbDev, Flushable};
+
+    #[test]
+    fn open_file() {
+        let path = std::env::temp_dir().join(format!("gfx-xtra-fbdev-{}.raw", std::process::id()));
+
+        {
+            let mut fb = FbDev::<Rgb565>::open_file(&path, Size::new(4, 3)).unwrap();
+
+            assert_eq!(fb.as_bytes().len(), 4 * 3 * 2);
+
+            fb.fill_solid(
+                &Rectangle::new(Point::new(1, 1), Size::new(2, 1)),
+                Rgb565::RED,
+            )
+            .unwrap();
+            fb.draw_iter([
+                Pixel(Point::new(3, 2), Rgb565::BLUE),
+                Pixel(Point::new(4, 2), Rgb565::RED),
+            ])
+            .unwrap();
+            fb.flush().unwrap();
+        }
+
+        let data = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut expected = vec![0; 4 * 3 * 2];
+        expected[(4 + 1) * 2..(4 + 3) * 2].copy_from_slice(&[0x00, 0xf8, 0x00, 0xf8]);
+        expected[(2 * 4 + 3) * 2..].copy_from_slice(&[0x1f, 0x00]);
+
+        assert_eq!(data, expected);
+    }
+}
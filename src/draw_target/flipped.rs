@@ -0,0 +1,367 @@
+use core::convert::Infallible;
+
+use embedded_graphics::framebuffer::Framebuffer as EgFramebuffer;
+use embedded_graphics::prelude::{
+    Dimensions, DrawTarget, IntoStorage, OriginDimensions, PixelColor, Point, Size,
+};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::fb::copy_rows;
+use super::{union, Flushable, PackedFramebuffer, StaticFramebuffer};
+
+pub trait PageBuffer: DrawTarget<Error = Infallible> + OriginDimensions {
+    fn copy_rows_from(&mut self, other: &Self, area: &Rectangle);
+}
+
+impl<'a, C> PageBuffer for PackedFramebuffer<'a, C>
+where
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    fn copy_rows_from(&mut self, other: &Self, area: &Rectangle) {
+        PackedFramebuffer::copy_rows_from(self, other, area)
+    }
+}
+
+impl<C, const W: usize, const H: usize, const N: usize> PageBuffer for StaticFramebuffer<C, W, H, N>
+where
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    fn copy_rows_from(&mut self, other: &Self, area: &Rectangle) {
+        StaticFramebuffer::copy_rows_from(self, other, area)
+    }
+}
+
+impl<C, BO, const W: usize, const H: usize, const N: usize> PageBuffer
+    for EgFramebuffer<C, C::Raw, BO, W, H, N>
+where
+    C: PixelColor,
+    Self: DrawTarget<Color = C, Error = Infallible>,
+{
+    fn copy_rows_from(&mut self, other: &Self, area: &Rectangle) {
+        let stride = other.data().len() / H.max(1);
+
+        copy_rows(
+            self.data_mut(),
+            other.data(),
+            stride,
+            Size::new(W as _, H as _),
+            area,
+        );
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PageSizeMismatch {
+    pub front: Size,
+    pub back: Size,
+}
+
+pub struct PageFlipped<B, F> {
+    buffers: [B; 2],
+    back: usize,
+    dirty: Option<Rectangle>,
+    copy_dirty: bool,
+    flip: F,
+}
+
+impl<B, F, E> PageFlipped<B, F>
+where
+    B: PageBuffer,
+    F: FnMut(&mut B) -> Result<(), E>,
+{
+    pub fn new(front: B, back: B, flip: F) -> Result<Self, PageSizeMismatch> {
+        if front.size() != back.size() {
+            return Err(PageSizeMismatch {
+                front: front.size(),
+                back: back.size(),
+            });
+        }
+
+        Ok(Self {
+            buffers: [front, back],
+            back: 1,
+            dirty: None,
+            copy_dirty: true,
+            flip,
+        })
+    }
+}
+
+impl<B, F> PageFlipped<B, F>
+where
+    B: PageBuffer,
+{
+    pub fn set_copy_dirty(&mut self, copy_dirty: bool) {
+        self.copy_dirty = copy_dirty;
+    }
+
+    pub fn front(&self) -> &B {
+        &self.buffers[1 - self.back]
+    }
+
+    pub fn back(&mut self) -> &mut B {
+        &mut self.buffers[self.back]
+    }
+
+    fn mark_dirty(&mut self, area: &Rectangle) {
+        let area = area.intersection(&self.bounding_box());
+
+        if area.is_zero_sized() {
+            return;
+        }
+
+        self.dirty = Some(if let Some(dirty) = self.dirty {
+            union(&dirty, &area)
+        } else {
+            area
+        });
+    }
+}
+
+impl<B, F> OriginDimensions for PageFlipped<B, F>
+where
+    B: PageBuffer,
+{
+    fn size(&self) -> Size {
+        self.buffers[0].size()
+    }
+}
+
+impl<B, F, E> DrawTarget for PageFlipped<B, F>
+where
+    B: PageBuffer,
+    F: FnMut(&mut B) -> Result<(), E>,
+{
+    type Error = E;
+
+    type Color = B::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bbox = self.bounding_box();
+
+        let mut top_left = Point::new(i32::MAX, i32::MAX);
+        let mut bottom_right = Point::new(i32::MIN, i32::MIN);
+
+        self.buffers[self.back]
+            .draw_iter(pixels.into_iter().filter(|pixel| {
+                if bbox.contains(pixel.0) {
+                    top_left = top_left.component_min(pixel.0);
+                    bottom_right = bottom_right.component_max(pixel.0);
+
+                    true
+                } else {
+                    false
+                }
+            }))
+            .unwrap();
+
+        if top_left.x <= bottom_right.x {
+            self.mark_dirty(&Rectangle::with_corners(top_left, bottom_right));
+        }
+
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.mark_dirty(area);
+
+        self.buffers[self.back]
+            .fill_contiguous(area, colors)
+            .unwrap();
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.mark_dirty(area);
+
+        self.buffers[self.back].fill_solid(area, color).unwrap();
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.mark_dirty(&self.bounding_box());
+
+        self.buffers[self.back].clear(color).unwrap();
+
+        Ok(())
+    }
+}
+
+impl<B, F, E> Flushable for PageFlipped<B, F>
+where
+    B: PageBuffer,
+    F: FnMut(&mut B) -> Result<(), E>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (self.flip)(&mut self.buffers[self.back])?;
+
+        self.back = 1 - self.back;
+
+        if let Some(dirty) = self.dirty.take() {
+            if self.copy_dirty {
+                let [first, second] = &mut self.buffers;
+
+                let (front, back) = if self.back == 0 {
+                    (second, first)
+                } else {
+                    (first, second)
+                };
+
+                back.copy_rows_from(front, &dirty);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use embedded_graphics::framebuffer::{buffer_size, Framebuffer};
+    use embedded_graphics::image::GetPixel;
+    use embedded_graphics::pixelcolor::raw::{LittleEndian, RawU16, RawU2};
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{DrawTarget, PixelColor, Point, RgbColor, Size};
+    use embedded_graphics::Pixel;
+
+    use super::{PageFlipped, PageSizeMismatch};
+    use crate::draw_target::{Flushable, PackedFramebuffer};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl PixelColor for Gray {
+        type Raw = RawU2;
+    }
+
+    impl From<Gray> for RawU2 {
+        fn from(color: Gray) -> Self {
+            RawU2::new(color.0)
+        }
+    }
+
+    impl From<u8> for Gray {
+        fn from(bits: u8) -> Self {
+            Gray(bits)
+        }
+    }
+
+    fn draw<D>(target: &mut D, x: i32, y: i32, color: D::Color)
+    where
+        D: DrawTarget,
+        D::Error: core::fmt::Debug,
+    {
+        target.draw_iter([Pixel(Point::new(x, y), color)]).unwrap();
+    }
+
+    #[test]
+    fn rejects_pages_of_different_sizes() {
+        let mut front = [0; 4];
+        let mut back = [0; 6];
+
+        let flipped = PageFlipped::new(
+            PackedFramebuffer::<Gray>::new(&mut front, 8, 2),
+            PackedFramebuffer::<Gray>::new(&mut back, 8, 3),
+            |_: &mut PackedFramebuffer<'_, Gray>| Ok::<_, ()>(()),
+        );
+
+        assert_eq!(
+            flipped.err(),
+            Some(PageSizeMismatch {
+                front: Size::new(8, 2),
+                back: Size::new(8, 3),
+            })
+        );
+    }
+
+    #[test]
+    fn flips_and_copies_the_dirty_rows_back() {
+        let mut front = [0; 8];
+        let mut back = [0; 8];
+
+        let flips = Cell::new(0);
+
+        let mut flipped = PageFlipped::new(
+            PackedFramebuffer::<Gray>::new(&mut front, 8, 4),
+            PackedFramebuffer::<Gray>::new(&mut back, 8, 4),
+            |page: &mut PackedFramebuffer<'_, Gray>| {
+                flips.set(flips.get() + 1);
+
+                // The flipped page is the one drawn into
+                assert_ne!(page.pixel(Point::new(1, 1)), Some(Gray(0)));
+
+                Ok::<_, ()>(())
+            },
+        )
+        .unwrap();
+
+        draw(&mut flipped, 1, 1, Gray(3));
+
+        assert_eq!(flipped.front().pixel(Point::new(1, 1)), Some(Gray(0)));
+        assert_eq!(flipped.back().pixel(Point::new(1, 1)), Some(Gray(3)));
+
+        flipped.flush().unwrap();
+
+        assert_eq!(flips.get(), 1);
+        assert_eq!(flipped.front().pixel(Point::new(1, 1)), Some(Gray(3)));
+        assert_eq!(flipped.back().pixel(Point::new(1, 1)), Some(Gray(3)));
+
+        draw(&mut flipped, 2, 3, Gray(2));
+        flipped.flush().unwrap();
+
+        for page in [
+            flipped.front().pixel(Point::new(2, 3)),
+            flipped.back().pixel(Point::new(2, 3)),
+        ] {
+            assert_eq!(page, Some(Gray(2)));
+        }
+
+        // Without copying, the new back page keeps its older contents
+        flipped.set_copy_dirty(false);
+
+        draw(&mut flipped, 0, 2, Gray(1));
+        flipped.flush().unwrap();
+
+        assert_eq!(flips.get(), 3);
+        assert_eq!(flipped.front().pixel(Point::new(0, 2)), Some(Gray(1)));
+        assert_eq!(flipped.back().pixel(Point::new(0, 2)), Some(Gray(0)));
+        assert_eq!(flipped.back().pixel(Point::new(1, 1)), Some(Gray(3)));
+    }
+
+    #[test]
+    fn copies_only_the_dirty_rows_of_rgb_pages() {
+        type Page =
+            Framebuffer<Rgb565, RawU16, LittleEndian, 4, 3, { buffer_size::<Rgb565>(4, 3) }>;
+
+        let mut flipped =
+            PageFlipped::new(Page::new(), Page::new(), |_: &mut Page| Ok::<_, ()>(())).unwrap();
+
+        // Diverge the pages outside of the rows drawn next
+        draw(&mut flipped, 0, 0, Rgb565::RED);
+        flipped.set_copy_dirty(false);
+        flipped.flush().unwrap();
+        flipped.set_copy_dirty(true);
+
+        draw(&mut flipped, 3, 2, Rgb565::GREEN);
+        draw(&mut flipped, 0, 1, Rgb565::BLUE);
+        flipped.flush().unwrap();
+
+        assert_eq!(flipped.front().pixel(Point::new(3, 2)), Some(Rgb565::GREEN));
+        assert_eq!(flipped.back().pixel(Point::new(3, 2)), Some(Rgb565::GREEN));
+        assert_eq!(flipped.back().pixel(Point::new(0, 1)), Some(Rgb565::BLUE));
+        assert_eq!(flipped.front().pixel(Point::new(0, 0)), Some(Rgb565::BLACK));
+        assert_eq!(flipped.back().pixel(Point::new(0, 0)), Some(Rgb565::RED));
+    }
+}
@@ -1,8 +1,8 @@
-use embedded_graphics::prelude::{Dimensions, DrawTarget};
+use embedded_graphics::prelude::{Dimensions, DrawTarget, Point};
 use embedded_graphics::primitives::Rectangle;
 use embedded_graphics::Pixel;
 
-use super::Flushable;
+use super::{identity_mapping, CoordinateMapping, Flushable};
 
 pub struct Flushing<'a, T, F> {
     parent: &'a mut T,
@@ -77,3 +77,16 @@ where
         self.parent.bounding_box()
     }
 }
+
+impl<'a, T, F> CoordinateMapping for Flushing<'a, T, F>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+}
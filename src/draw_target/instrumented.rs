@@ -0,0 +1,395 @@
+use core::time::Duration;
+
+use embedded_graphics::prelude::{Dimensions, DrawTarget, PixelColor, Point, RawData};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{Clock, DisplayMapping, Flushable, Refresh};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FlushStats {
+    pub refresh: Refresh,
+    pub changed_pixels: usize,
+    pub changed_rects: usize,
+    pub bytes_sent: usize,
+    pub duration: Duration,
+}
+
+impl Default for FlushStats {
+    fn default() -> Self {
+        Self {
+            refresh: Refresh::Partial,
+            changed_pixels: 0,
+            changed_rects: 0,
+            bytes_sent: 0,
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+pub trait FlushObserver {
+    fn flushed(&mut self, stats: &FlushStats);
+}
+
+impl<F> FlushObserver for F
+where
+    F: FnMut(&FlushStats),
+{
+    fn flushed(&mut self, stats: &FlushStats) {
+        self(stats)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FlushCounters {
+    pub full_flushes: usize,
+    pub partial_flushes: usize,
+    pub changed_pixels: usize,
+    pub changed_rects: usize,
+    pub bytes_sent: usize,
+    pub duration: Duration,
+}
+
+impl FlushObserver for FlushCounters {
+    fn flushed(&mut self, stats: &FlushStats) {
+        match stats.refresh {
+            Refresh::Full => self.full_flushes += 1,
+            Refresh::Partial => self.partial_flushes += 1,
+        }
+
+        self.changed_pixels += stats.changed_pixels;
+        self.changed_rects += stats.changed_rects;
+        self.bytes_sent += stats.bytes_sent;
+        self.duration += stats.duration;
+    }
+}
+
+pub struct Instrumented<T, C, O> {
+    target: T,
+    clock: C,
+    observer: O,
+    counter: Counter,
+    last: FlushStats,
+}
+
+impl<T, C, O> Instrumented<T, C, O>
+where
+    T: Flushable,
+    C: Clock,
+    O: FlushObserver,
+{
+    pub(crate) fn new(target: T, clock: C, observer: O) -> Self {
+        Self {
+            target,
+            clock,
+            observer,
+            counter: Counter {
+                stats: FlushStats::default(),
+                run: None,
+                bits_per_pixel: <T::Color as PixelColor>::Raw::BITS_PER_PIXEL,
+            },
+            last: FlushStats::default(),
+        }
+    }
+
+    pub fn last_stats(&self) -> &FlushStats {
+        &self.last
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.target
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.target
+    }
+
+    pub fn into_inner(self) -> T {
+        self.target
+    }
+}
+
+impl<T, C, O> DrawTarget for Instrumented<T, C, O>
+where
+    T: Flushable,
+    C: Clock,
+    O: FlushObserver,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bbox = self.target.bounding_box();
+        let counter = &mut self.counter;
+
+        self.target.draw_iter(pixels.into_iter().inspect(|pixel| {
+            if bbox.contains(pixel.0) {
+                counter.add_pixel(pixel.0);
+            }
+        }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.counter
+            .add_rect(&area.intersection(&self.target.bounding_box()));
+
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.counter
+            .add_rect(&area.intersection(&self.target.bounding_box()));
+
+        self.target.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.counter.add_rect(&self.target.bounding_box());
+
+        self.target.clear(color)
+    }
+}
+
+impl<T, C, O> Dimensions for Instrumented<T, C, O>
+where
+    T: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<T, C, O> DisplayMapping for Instrumented<T, C, O>
+where
+    T: DisplayMapping,
+{
+    fn map_from_display(&self, point: Point) -> Option<Point> {
+        self.target.map_from_display(point)
+    }
+
+    fn map_to_display(&self, point: Point) -> Option<Point> {
+        self.target.map_to_display(point)
+    }
+}
+
+impl<T, C, O> Flushable for Instrumented<T, C, O>
+where
+    T: Flushable,
+    C: Clock,
+    O: FlushObserver,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_with(Refresh::Partial)
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        let start = self.clock.now();
+
+        let result = self.target.flush_with(refresh);
+
+        self.last = self.counter.take();
+        self.last.refresh = refresh;
+        self.last.duration = self.clock.now().saturating_sub(start);
+
+        debug!("Display flushed: {:?}", self.last);
+
+        self.observer.flushed(&self.last);
+
+        result
+    }
+}
+
+struct Counter {
+    stats: FlushStats,
+    run: Option<(Point, usize)>,
+    bits_per_pixel: usize,
+}
+
+impl Counter {
+    fn add_rect(&mut self, area: &Rectangle) {
+        if !area.is_zero_sized() {
+            let pixels = (area.size.width * area.size.height) as usize;
+
+            self.end_run();
+
+            self.stats.changed_pixels += pixels;
+            self.stats.changed_rects += 1;
+            self.stats.bytes_sent += self.bytes(pixels);
+        }
+    }
+
+    fn add_pixel(&mut self, point: Point) {
+        self.stats.changed_pixels += 1;
+
+        if let Some((last, len)) = self.run {
+            if last.y == point.y && last.x + 1 == point.x {
+                self.run = Some((point, len + 1));
+
+                return;
+            }
+        }
+
+        self.end_run();
+        self.run = Some((point, 1));
+    }
+
+    fn end_run(&mut self) {
+        if let Some((_, len)) = self.run.take() {
+            self.stats.changed_rects += 1;
+            self.stats.bytes_sent += self.bytes(len);
+        }
+    }
+
+    fn take(&mut self) -> FlushStats {
+        self.end_run();
+
+        core::mem::take(&mut self.stats)
+    }
+
+    fn bytes(&self, pixels: usize) -> usize {
+        (pixels * self.bits_per_pixel + 7) / 8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use core::time::Duration;
+
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Point, Size};
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{Clock, FlushCounters, FlushStats, Flushable, Refresh};
+    use crate::draw_target::OwnedDrawTargetExt;
+
+    struct TestClock<'a>(&'a Cell<Duration>);
+
+    impl<'a> Clock for TestClock<'a> {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    // A display which takes `flush_time` to flush
+    struct Slow<'a> {
+        clock: &'a Cell<Duration>,
+        flush_time: Duration,
+    }
+
+    impl<'a> OriginDimensions for Slow<'a> {
+        fn size(&self) -> Size {
+            Size::new(8, 4)
+        }
+    }
+
+    impl<'a> DrawTarget for Slow<'a> {
+        type Error = Infallible;
+
+        type Color = BinaryColor;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            pixels.into_iter().for_each(drop);
+
+            Ok(())
+        }
+    }
+
+    impl<'a> Flushable for Slow<'a> {
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.clock.set(self.clock.get() + self.flush_time);
+
+            Ok(())
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn measures_the_flush_of_the_display() {
+        let clock = Cell::new(ms(1000));
+
+        let mut display = Slow {
+            clock: &clock,
+            flush_time: ms(5),
+        }
+        .owned_instrumented(TestClock(&clock), FlushCounters::default());
+
+        let on = |x, y| Pixel(Point::new(x, y), BinaryColor::On);
+
+        display
+            .draw_iter([on(0, 0), on(1, 0), on(2, 0), on(0, 1), on(9, 9)])
+            .unwrap();
+
+        // The time spent drawing before the flush is not part of it
+        clock.set(clock.get() + ms(100));
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(4, 2), Size::new(8, 8)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        display.flush_with(Refresh::Full).unwrap();
+
+        assert_eq!(
+            display.last_stats(),
+            &FlushStats {
+                refresh: Refresh::Full,
+                changed_pixels: 12,
+                changed_rects: 3,
+                bytes_sent: 3,
+                duration: ms(5),
+            }
+        );
+
+        clock.set(clock.get() + ms(50));
+        display.inner_mut().flush_time = ms(7);
+
+        display.flush().unwrap();
+
+        assert_eq!(
+            display.last_stats(),
+            &FlushStats {
+                duration: ms(7),
+                ..FlushStats::default()
+            }
+        );
+
+        assert_eq!(
+            display.observer(),
+            &FlushCounters {
+                full_flushes: 1,
+                partial_flushes: 1,
+                changed_pixels: 12,
+                changed_rects: 3,
+                bytes_sent: 3,
+                duration: ms(12),
+            }
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use embedded_graphics::prelude::{
+    Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, Size,
+};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{surrounding, CoordinateMapping, Flushable, Refresh};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Letterbox<C> {
+    pub size: Size,
+    pub border: C,
+}
+
+impl<C> Letterbox<C> {
+    pub const fn new(size: Size, border: C) -> Self {
+        Self { size, border }
+    }
+
+    pub fn scale(&self, pdim: &Rectangle) -> u32 {
+        self.fitting_scale(pdim).max(1)
+    }
+
+    pub fn area(&self, pdim: &Rectangle) -> Rectangle {
+        self.area_scaled(pdim, self.scale(pdim))
+    }
+
+    fn fitting_scale(&self, pdim: &Rectangle) -> u32 {
+        if self.size.width == 0 || self.size.height == 0 {
+            0
+        } else {
+            (pdim.size.width / self.size.width).min(pdim.size.height / self.size.height)
+        }
+    }
+
+    fn area_scaled(&self, pdim: &Rectangle, scale: u32) -> Rectangle {
+        let size = self.size * scale;
+
+        Rectangle::new(
+            pdim.top_left
+                + Point::new(
+                    (pdim.size.width as i32 - size.width as i32) / 2,
+                    (pdim.size.height as i32 - size.height as i32) / 2,
+                ),
+            size,
+        )
+    }
+
+    fn transform_point(&self, point: Point, scale: u32, area: &Rectangle) -> Point {
+        area.top_left + point * scale as i32
+    }
+
+    fn transform_rect(&self, rect: &Rectangle, scale: u32, area: &Rectangle) -> Rectangle {
+        let rect = rect.intersection(&Rectangle::new(Point::zero(), self.size));
+
+        Rectangle::new(
+            self.transform_point(rect.top_left, scale, area),
+            rect.size * scale,
+        )
+    }
+
+    pub(crate) fn map_to_logical(&self, point: Point, pdim: &Rectangle) -> Option<Point> {
+        let scale = self.scale(pdim);
+
+        self.map_to_logical_scaled(point, scale, &self.area_scaled(pdim, scale))
+    }
+
+    pub(crate) fn map_to_physical(&self, point: Point, pdim: &Rectangle) -> Option<Point> {
+        let scale = self.scale(pdim);
+
+        self.map_to_physical_scaled(point, scale, &self.area_scaled(pdim, scale))
+    }
+
+    fn map_to_logical_scaled(&self, point: Point, scale: u32, area: &Rectangle) -> Option<Point> {
+        area.contains(point)
+            .then(|| (point - area.top_left) / scale as i32)
+    }
+
+    fn map_to_physical_scaled(&self, point: Point, scale: u32, area: &Rectangle) -> Option<Point> {
+        Rectangle::new(Point::zero(), self.size)
+            .contains(point)
+            .then(|| self.transform_point(point, scale, area))
+    }
+}
+
+pub struct Letterboxed<'a, T>
+where
+    T: DrawTarget,
+{
+    parent: &'a mut T,
+    letterbox: Letterbox<T::Color>,
+    scale: u32,
+    area: Rectangle,
+}
+
+impl<'a, T> Letterboxed<'a, T>
+where
+    T: DrawTarget,
+{
+    pub(crate) fn new(parent: &'a mut T, letterbox: Letterbox<T::Color>) -> Self {
+        let pdim = parent.bounding_box();
+
+        if letterbox.fitting_scale(&pdim) == 0 {
+            warn!(
+                "Logical size {:?} does not fit in {:?}",
+                letterbox.size, pdim.size
+            );
+        }
+
+        let scale = letterbox.scale(&pdim);
+
+        Self {
+            scale,
+            area: letterbox.area_scaled(&pdim, scale),
+            parent,
+            letterbox,
+        }
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<'a, T> CoordinateMapping for Letterboxed<'a, T>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.letterbox
+            .map_to_logical_scaled(point, self.scale, &self.area)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.letterbox
+            .map_to_physical_scaled(point, self.scale, &self.area)
+    }
+}
+
+impl<'a, T> DrawTarget for Letterboxed<'a, T>
+where
+    T: DrawTarget,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bbox = self.bounding_box();
+        let pixels = pixels.into_iter().filter(|pixel| bbox.contains(pixel.0));
+
+        if self.scale == 1 {
+            let offset = self.area.top_left;
+
+            self.parent
+                .draw_iter(pixels.map(|pixel| Pixel(pixel.0 + offset, pixel.1)))
+        } else {
+            for pixel in pixels {
+                let block = Rectangle::new(
+                    self.letterbox
+                        .transform_point(pixel.0, self.scale, &self.area),
+                    Size::new_equal(self.scale),
+                );
+
+                self.parent.fill_solid(&block, pixel.1)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.scale == 1 && self.bounding_box().intersection(area) == *area {
+            self.parent.fill_contiguous(
+                &Rectangle::new(area.top_left + self.area.top_left, area.size),
+                colors,
+            )
+        } else {
+            self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            )
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.letterbox.transform_rect(area, self.scale, &self.area);
+
+        if area.is_zero_sized() {
+            Ok(())
+        } else {
+            self.parent.fill_solid(&area, color)
+        }
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let pdim = self.parent.bounding_box();
+
+        for border in surrounding(&pdim, &pdim.intersection(&self.area)) {
+            self.parent.fill_solid(&border, self.letterbox.border)?;
+        }
+
+        self.parent
+            .fill_solid(&self.area.intersection(&pdim), color)
+    }
+}
+
+impl<'a, T> OriginDimensions for Letterboxed<'a, T>
+where
+    T: DrawTarget,
+{
+    fn size(&self) -> Size {
+        self.letterbox.size
+    }
+}
+
+impl<'a, T> Flushable for Letterboxed<'a, T>
+where
+    T: Flushable,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, RgbColor, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::Letterbox;
+    use crate::draw_target::{CoordinateMapping, DrawTargetExt2};
+
+    struct Display {
+        size: Size,
+        pixels: [[Rgb565; 10]; 7],
+    }
+
+    impl Display {
+        fn new(width: u32, height: u32) -> Self {
+            Self {
+                size: Size::new(width, height),
+                pixels: [[Rgb565::WHITE; 10]; 7],
+            }
+        }
+
+        fn pixel(&self, x: i32, y: i32) -> Rgb565 {
+            self.pixels[y as usize][x as usize]
+        }
+    }
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            self.size
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Color = Rgb565;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                if self.bounding_box().contains(point) {
+                    self.pixels[point.y as usize][point.x as usize] = color;
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn centers_the_largest_fitting_scale() {
+        let letterbox = Letterbox::new(Size::new(4, 3), Rgb565::RED);
+
+        assert_eq!(letterbox.scale(&rect(0, 0, 10, 7)), 2);
+        assert_eq!(letterbox.area(&rect(0, 0, 10, 7)), rect(1, 0, 8, 6));
+
+        assert_eq!(letterbox.scale(&rect(5, 5, 13, 7)), 2);
+        assert_eq!(letterbox.area(&rect(5, 5, 13, 7)), rect(7, 5, 8, 6));
+
+        assert_eq!(letterbox.scale(&rect(0, 0, 12, 9)), 3);
+        assert_eq!(letterbox.area(&rect(0, 0, 12, 9)), rect(0, 0, 12, 9));
+    }
+
+    #[test]
+    fn upscales_pixels_to_blocks() {
+        let mut display = Display::new(10, 7);
+        let mut letterboxed = display.letterboxed(Size::new(4, 3), Rgb565::RED);
+
+        assert_eq!(letterboxed.scale(), 2);
+        assert_eq!(letterboxed.bounding_box(), rect(0, 0, 4, 3));
+
+        assert_eq!(
+            letterboxed.map_to_logical(Point::new(4, 5)),
+            Some(Point::new(1, 2))
+        );
+        assert_eq!(letterboxed.map_to_logical(Point::new(0, 0)), None);
+        assert_eq!(
+            letterboxed.map_to_physical(Point::new(1, 2)),
+            Some(Point::new(3, 4))
+        );
+        assert_eq!(letterboxed.map_to_physical(Point::new(4, 0)), None);
+
+        letterboxed
+            .draw_iter([
+                Pixel(Point::new(1, 2), Rgb565::GREEN),
+                Pixel(Point::new(4, 2), Rgb565::GREEN),
+            ])
+            .unwrap();
+        letterboxed
+            .fill_contiguous(&rect(3, 0, 2, 1), [Rgb565::BLUE, Rgb565::BLUE])
+            .unwrap();
+        letterboxed
+            .fill_solid(&rect(-1, -1, 2, 2), Rgb565::YELLOW)
+            .unwrap();
+
+        for point in display.bounding_box().points() {
+            let expected = if rect(3, 4, 2, 2).contains(point) {
+                Rgb565::GREEN
+            } else if rect(7, 0, 2, 2).contains(point) {
+                Rgb565::BLUE
+            } else if rect(1, 0, 2, 2).contains(point) {
+                Rgb565::YELLOW
+            } else {
+                Rgb565::WHITE
+            };
+
+            assert_eq!(display.pixel(point.x, point.y), expected);
+        }
+    }
+
+    #[test]
+    fn clears_the_borders() {
+        let mut display = Display::new(10, 7);
+
+        display
+            .letterboxed(Size::new(4, 3), Rgb565::RED)
+            .clear(Rgb565::BLUE)
+            .unwrap();
+
+        for point in display.bounding_box().points() {
+            let expected = if rect(1, 0, 8, 6).contains(point) {
+                Rgb565::BLUE
+            } else {
+                Rgb565::RED
+            };
+
+            assert_eq!(display.pixel(point.x, point.y), expected);
+        }
+    }
+
+    #[test]
+    fn draws_unscaled_when_the_logical_size_does_not_fit() {
+        let mut display = Display::new(3, 2);
+        let mut letterboxed = display.letterboxed(Size::new(4, 3), Rgb565::RED);
+
+        assert_eq!(letterboxed.scale(), 1);
+        assert_eq!(letterboxed.area(), rect(0, 0, 4, 3));
+
+        letterboxed.clear(Rgb565::BLUE).unwrap();
+        letterboxed
+            .draw_iter([
+                Pixel(Point::new(1, 1), Rgb565::GREEN),
+                Pixel(Point::new(3, 2), Rgb565::GREEN),
+            ])
+            .unwrap();
+
+        for point in display.bounding_box().points() {
+            let expected = if point == Point::new(1, 1) {
+                Rgb565::GREEN
+            } else {
+                Rgb565::BLUE
+            };
+
+            assert_eq!(display.pixel(point.x, point.y), expected);
+        }
+    }
+}
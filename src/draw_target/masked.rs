@@ -0,0 +1,331 @@
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::{Dimensions, DrawTarget, IntoStorage, PixelColor, Point, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{identity_mapping, CoordinateMapping, Flushable, PackedFramebuffer, Refresh};
+
+pub trait PixelMask<C> {
+    fn is_drawn(&self, point: Point, color: C) -> bool;
+
+    fn fill_drawn<D>(&self, target: &mut D, area: &Rectangle, color: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: Copy,
+    {
+        let area = area.intersection(&target.bounding_box());
+
+        for y in area.rows() {
+            let mut span: Option<(i32, u32)> = None;
+
+            for x in area.columns() {
+                if self.is_drawn(Point::new(x, y), color) {
+                    span = Some(span.map_or((x, 1), |(start, width)| (start, width + 1)));
+                } else {
+                    fill_span(target, y, span.take(), color)?;
+                }
+            }
+
+            fill_span(target, y, span, color)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn fill_span<D>(
+    target: &mut D,
+    y: i32,
+    span: Option<(i32, u32)>,
+    color: D::Color,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget,
+{
+    if let Some((start, width)) = span {
+        target.fill_solid(
+            &Rectangle::new(Point::new(start, y), Size::new(width, 1)),
+            color,
+        )
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct KeyColor<C>(pub C);
+
+impl<C> PixelMask<C> for KeyColor<C>
+where
+    C: PixelColor,
+{
+    fn is_drawn(&self, _point: Point, color: C) -> bool {
+        color != self.0
+    }
+
+    fn fill_drawn<D>(&self, target: &mut D, area: &Rectangle, color: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        if color != self.0 {
+            target.fill_solid(area, color)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'a, S, C> PixelMask<C> for PackedFramebuffer<'a, S>
+where
+    S: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    fn is_drawn(&self, point: Point, _color: C) -> bool {
+        self.pixel(point)
+            .map_or(false, |stencil| stencil.into_storage() != 0)
+    }
+
+    fn fill_drawn<D>(&self, target: &mut D, area: &Rectangle, color: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: Copy,
+    {
+        let pixels_per_byte = PackedFramebuffer::<S>::PIXELS_PER_BYTE as i32;
+
+        let area = area
+            .intersection(&target.bounding_box())
+            .intersection(&self.bounding_box());
+        let end = area.top_left.x + area.size.width as i32;
+
+        for y in area.rows() {
+            let row = self.row_bytes(y as _);
+            let mut span: Option<(i32, u32)> = None;
+            let mut x = area.top_left.x;
+
+            while x < end {
+                let byte = row[(x / pixels_per_byte) as usize];
+
+                if byte == 0 {
+                    fill_span(target, y, span.take(), color)?;
+
+                    x = (x / pixels_per_byte + 1) * pixels_per_byte;
+                } else {
+                    let stencil =
+                        PackedFramebuffer::<S>::pixel_in_byte(byte, (x % pixels_per_byte) as usize);
+
+                    if stencil.into_storage() != 0 {
+                        span = Some(span.map_or((x, 1), |(start, width)| (start, width + 1)));
+                    } else {
+                        fill_span(target, y, span.take(), color)?;
+                    }
+
+                    x += 1;
+                }
+            }
+
+            fill_span(target, y, span, color)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Masked<'a, T, M>
+where
+    T: DrawTarget,
+{
+    parent: &'a mut T,
+    mask: &'a M,
+}
+
+impl<'a, T, M> Masked<'a, T, M>
+where
+    T: DrawTarget,
+    M: PixelMask<T::Color>,
+{
+    pub(crate) fn new(parent: &'a mut T, mask: &'a M) -> Self {
+        Self { parent, mask }
+    }
+}
+
+impl<'a, T, M> DrawTarget for Masked<'a, T, M>
+where
+    T: DrawTarget,
+    M: PixelMask<T::Color>,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mask = self.mask;
+
+        self.parent.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|pixel| mask.is_drawn(pixel.0, pixel.1)),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.mask.fill_drawn(self.parent, area, color)
+    }
+}
+
+impl<'a, T, M> Dimensions for Masked<'a, T, M>
+where
+    T: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<'a, T, M> CoordinateMapping for Masked<'a, T, M>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+}
+
+impl<'a, T, M> Flushable for Masked<'a, T, M>
+where
+    T: Flushable,
+    M: PixelMask<T::Color>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::raw::RawU1;
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{DrawTarget, OriginDimensions, PixelColor, Point, Size};
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::PixelMask;
+    use crate::draw_target::PackedFramebuffer;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Bit(u8);
+
+    impl PixelColor for Bit {
+        type Raw = RawU1;
+    }
+
+    impl From<Bit> for RawU1 {
+        fn from(color: Bit) -> Self {
+            RawU1::new(color.0)
+        }
+    }
+
+    impl From<u8> for Bit {
+        fn from(bits: u8) -> Self {
+            Bit(bits)
+        }
+    }
+
+    // Uses the per-pixel `fill_drawn` of the trait
+    struct PerPixel<'a, 'b>(&'a PackedFramebuffer<'b, Bit>);
+
+    impl<'a, 'b, C> PixelMask<C> for PerPixel<'a, 'b> {
+        fn is_drawn(&self, point: Point, color: C) -> bool {
+            self.0.is_drawn(point, color)
+        }
+    }
+
+    #[derive(PartialEq, Debug)]
+    struct Spans {
+        spans: [Option<Rectangle>; 32],
+        count: usize,
+    }
+
+    impl OriginDimensions for Spans {
+        fn size(&self) -> Size {
+            Size::new(32, 6)
+        }
+    }
+
+    impl DrawTarget for Spans {
+        type Color = BinaryColor;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            unreachable!()
+        }
+
+        fn fill_solid(&mut self, area: &Rectangle, _color: Self::Color) -> Result<(), Self::Error> {
+            self.spans[self.count] = Some(*area);
+            self.count += 1;
+
+            Ok(())
+        }
+    }
+
+    fn spans<M>(mask: &M, area: &Rectangle) -> Spans
+    where
+        M: PixelMask<BinaryColor>,
+    {
+        let mut spans = Spans {
+            spans: [None; 32],
+            count: 0,
+        };
+
+        mask.fill_drawn(&mut spans, area, BinaryColor::On).unwrap();
+
+        spans
+    }
+
+    #[test]
+    fn fills_the_same_spans_as_per_pixel() {
+        let mut buf = [
+            0x00, 0x00, 0x00, //
+            0xff, 0x00, 0x81, //
+            0x0f, 0xf0, 0x5a, //
+            0x00, 0xff, 0x00, //
+        ];
+        let stencil = PackedFramebuffer::<Bit>::new(&mut buf, 24, 4);
+
+        let areas = [
+            Rectangle::new(Point::new(3, 0), Size::new(18, 4)),
+            Rectangle::new(Point::new(9, 1), Size::new(5, 3)),
+            Rectangle::new(Point::new(20, 1), Size::new(10, 4)),
+            Rectangle::new(Point::new(-2, -1), Size::new(40, 8)),
+            Rectangle::new(Point::new(1, 0), Size::new(6, 1)),
+        ];
+
+        for area in areas {
+            let packed = spans(&stencil, &area);
+
+            assert_eq!(packed, spans(&PerPixel(&stencil), &area), "{:?}", area);
+        }
+
+        assert_eq!(
+            spans(&stencil, &areas[1]).spans[..3],
+            [
+                Some(Rectangle::new(Point::new(12, 2), Size::new(2, 1))),
+                Some(Rectangle::new(Point::new(9, 3), Size::new(5, 1))),
+                None,
+            ]
+        );
+        assert_eq!(spans(&stencil, &areas[4]).count, 0);
+    }
+}
@@ -5,7 +5,11 @@ use embedded_graphics::prelude::{Dimensions, DrawTarget, PixelColor, Point, Size
 use embedded_graphics::primitives::Rectangle;
 use embedded_graphics::Pixel;
 
-use super::{DrawTargetExt2, Flushable, Flushing, RotateAngle, Rotated, Scaled};
+use super::{
+    identity_mapping, AffineTransform, ColorMap, ColorMapped, CoordinateMapping, DisplayMapping,
+    DrawTargetExt2, Flushable, Flushing, Letterbox, Letterboxed, Masked, PixelMask, Refresh,
+    RotateAngle, Rotated, RoundClipped, RowSpans, Scaled, Transformed,
+};
 
 pub trait Transformer {
     type Color: PixelColor;
@@ -25,78 +29,206 @@ pub trait Transformer {
     }
 }
 
-pub struct TranslatedT<T>(pub(crate) T, pub(crate) Point);
+pub trait FlushableTransformer: Transformer {
+    fn flush(&mut self) -> Result<(), Self::Error>;
 
-impl<T> Transformer for TranslatedT<T>
+    fn flush_with(&mut self, _refresh: Refresh) -> Result<(), Self::Error> {
+        self.flush()
+    }
+}
+
+impl<T> FlushableTransformer for T
 where
-    T: DrawTarget,
+    T: Transformer,
+    for<'a> T::DrawTarget<'a>: Flushable,
 {
-    type Color = T::Color;
-    type Error = T::Error;
-
-    type DrawTarget<'a> = Translated<'a, T> where Self: 'a;
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.transform().flush()
+    }
 
-    fn transform(&mut self) -> Self::DrawTarget<'_> {
-        self.0.translated(self.1)
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.transform().flush_with(refresh)
     }
 }
 
-impl<T> Flushable for Owned<TranslatedT<T>>
+pub trait TransformerParams: Transformer {
+    type Inner;
+    type Params;
+
+    fn inner(&self) -> &Self::Inner;
+
+    fn inner_mut(&mut self) -> &mut Self::Inner;
+
+    fn into_inner(self) -> Self::Inner;
+
+    fn params(&self) -> &Self::Params;
+
+    fn params_mut(&mut self) -> &mut Self::Params;
+}
+
+#[macro_export]
+macro_rules! transformer {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident<$t:ident $(, $g:ident)* $(,)?>($tvis:vis $tf:ident, $pvis:vis $param:ty)
+        $(where { $($bounds:tt)* })?
+        => for<$lt:lifetime> $dt:ty,
+        |$target:pat_param, $arg:pat_param| $body:expr $(,)?
+    ) => {
+        $(#[$attr])*
+        $vis struct $name<$t $(, $g)*>($tvis $tf, $pvis $param);
+
+        impl<$t $(, $g)*> $crate::draw_target::Transformer for $name<$t $(, $g)*>
+        where
+            $t: $crate::__embedded_graphics::draw_target::DrawTarget,
+            $($($bounds)*)?
+        {
+            type Color = <$t as $crate::__embedded_graphics::draw_target::DrawTarget>::Color;
+            type Error = <$t as $crate::__embedded_graphics::draw_target::DrawTarget>::Error;
+
+            type DrawTarget<$lt> = $dt where Self: $lt;
+
+            fn transform(&mut self) -> Self::DrawTarget<'_> {
+                let $target = &mut self.0;
+                let $arg = &self.1;
+
+                $body
+            }
+        }
+
+        impl<$t $(, $g)*> $crate::draw_target::TransformerParams for $name<$t $(, $g)*>
+        where
+            $t: $crate::__embedded_graphics::draw_target::DrawTarget,
+            $($($bounds)*)?
+        {
+            type Inner = $tf;
+            type Params = $param;
+
+            fn inner(&self) -> &Self::Inner {
+                &self.0
+            }
+
+            fn inner_mut(&mut self) -> &mut Self::Inner {
+                &mut self.0
+            }
+
+            fn into_inner(self) -> Self::Inner {
+                self.0
+            }
+
+            fn params(&self) -> &Self::Params {
+                &self.1
+            }
+
+            fn params_mut(&mut self) -> &mut Self::Params {
+                &mut self.1
+            }
+        }
+    };
+}
+
+transformer! {
+    pub struct TranslatedT<T>(pub(crate) T, pub(crate) Point) => for<'a> Translated<'a, T>,
+    |target, offset| target.translated(*offset)
+}
+
+impl<T> FlushableTransformer for TranslatedT<T>
 where
     T: Flushable,
 {
     fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0 .0.flush()
+        self.0.flush()
     }
-}
 
-pub struct CroppedT<T>(pub(crate) T, pub(crate) Rectangle);
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.0.flush_with(refresh)
+    }
+}
 
-impl<T> Transformer for CroppedT<T>
+impl<T> CoordinateMapping for TranslatedT<T>
 where
     T: DrawTarget,
 {
-    type Color = T::Color;
-    type Error = T::Error;
-
-    type DrawTarget<'a> = Cropped<'a, T> where Self: 'a;
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .translate(self.1)
+            .map_to_logical(point)
+    }
 
-    fn transform(&mut self) -> Self::DrawTarget<'_> {
-        self.0.cropped(&self.1)
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .translate(self.1)
+            .map_to_physical(point)
     }
 }
 
-impl<T> Flushable for Owned<CroppedT<T>>
+transformer! {
+    pub struct CroppedT<T>(pub(crate) T, pub(crate) Rectangle) => for<'a> Cropped<'a, T>,
+    |target, area| target.cropped(area)
+}
+
+impl<T> FlushableTransformer for CroppedT<T>
 where
     T: Flushable,
 {
     fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0 .0.flush()
+        self.0.flush()
     }
-}
 
-pub struct ClippedT<T>(pub(crate) T, pub(crate) Rectangle);
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.0.flush_with(refresh)
+    }
+}
 
-impl<T> Transformer for ClippedT<T>
+impl<T> CoordinateMapping for CroppedT<T>
 where
     T: DrawTarget,
 {
-    type Color = T::Color;
-    type Error = T::Error;
-
-    type DrawTarget<'a> = Clipped<'a, T> where Self: 'a;
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .crop(&self.1)
+            .map_to_logical(point)
+    }
 
-    fn transform(&mut self) -> Self::DrawTarget<'_> {
-        self.0.clipped(&self.1)
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .crop(&self.1)
+            .map_to_physical(point)
     }
 }
 
-impl<T> Flushable for Owned<ClippedT<T>>
+transformer! {
+    pub struct ClippedT<T>(pub(crate) T, pub(crate) Rectangle) => for<'a> Clipped<'a, T>,
+    |target, area| target.clipped(area)
+}
+
+impl<T> FlushableTransformer for ClippedT<T>
 where
     T: Flushable,
 {
     fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0 .0.flush()
+        self.0.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.0.flush_with(refresh)
+    }
+}
+
+impl<T> CoordinateMapping for ClippedT<T>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .clip(&self.1)
+            .map_to_logical(point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .clip(&self.1)
+            .map_to_physical(point)
     }
 }
 
@@ -117,63 +249,194 @@ where
     }
 }
 
-impl<T, C> Flushable for Owned<ColorConvertedT<T, C>>
+impl<T, C> TransformerParams for ColorConvertedT<T, C>
+where
+    T: DrawTarget,
+    C: PixelColor + Into<T::Color>,
+{
+    type Inner = T;
+    type Params = PhantomData<C>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.0
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.0
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+
+    fn params(&self) -> &Self::Params {
+        &self.1
+    }
+
+    fn params_mut(&mut self) -> &mut Self::Params {
+        &mut self.1
+    }
+}
+
+impl<T, C> FlushableTransformer for ColorConvertedT<T, C>
 where
     T: Flushable,
     C: PixelColor + Into<T::Color>,
 {
     fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0 .0.flush()
+        self.0.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.0.flush_with(refresh)
     }
 }
 
-pub struct RotatedT<T>(pub(crate) T, pub(crate) RotateAngle);
+impl<T, C> CoordinateMapping for ColorConvertedT<T, C>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
 
-impl<T> Transformer for RotatedT<T>
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
+}
+
+transformer! {
+    pub struct RotatedT<T>(pub(crate) T, pub(crate) RotateAngle) => for<'a> Rotated<'a, T>,
+    |target, angle| target.rotated(*angle)
+}
+
+impl<T> CoordinateMapping for RotatedT<T>
 where
     T: DrawTarget,
 {
-    type Color = T::Color;
-    type Error = T::Error;
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.1.map_to_logical(point, &self.0.bounding_box())
+    }
 
-    type DrawTarget<'a> = Rotated<'a, T> where Self: 'a;
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.1.map_to_physical(point, &self.0.bounding_box())
+    }
+}
 
-    fn transform(&mut self) -> Self::DrawTarget<'_> {
-        self.0.rotated(self.1)
+transformer! {
+    pub struct ScaledT<T>(pub(crate) T, pub(crate) Size) => for<'a> Scaled<'a, T>,
+    |target, size| target.scaled(*size)
+}
+
+impl<T> CoordinateMapping for ScaledT<T>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .scale(self.1)
+            .map_to_logical(point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        AffineTransform::new(self.0.bounding_box())
+            .scale(self.1)
+            .map_to_physical(point)
     }
 }
 
-impl<T> Flushable for Owned<RotatedT<T>>
+transformer! {
+    pub struct LetterboxedT<T, C>(pub(crate) T, pub(crate) Letterbox<C>)
+    where { T: DrawTarget<Color = C>, C: PixelColor }
+    => for<'a> Letterboxed<'a, T>,
+    |target, letterbox| target.letterboxed(letterbox.size, letterbox.border)
+}
+
+impl<T, C> CoordinateMapping for LetterboxedT<T, C>
 where
-    T: Flushable,
+    T: DrawTarget<Color = C>,
 {
-    fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0 .0.flush()
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.1.map_to_logical(point, &self.0.bounding_box())
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.1.map_to_physical(point, &self.0.bounding_box())
     }
 }
 
-pub struct ScaledT<T>(pub(crate) T, pub(crate) Size);
+transformer! {
+    pub struct TransformedT<T>(pub(crate) T, pub(crate) AffineTransform) => for<'a> Transformed<'a, T>,
+    |target, transform| target.transformed(transform)
+}
 
-impl<T> Transformer for ScaledT<T>
+impl<T> CoordinateMapping for TransformedT<T> {
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.1.map_to_logical(point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.1.map_to_physical(point)
+    }
+}
+
+transformer! {
+    pub struct ColorMappedT<T, M>(pub(crate) T, pub(crate) M)
+    where { M: ColorMap<T::Color> }
+    => for<'a> ColorMapped<'a, T, M>,
+    |target, map| target.color_mapped(map)
+}
+
+impl<T, M> CoordinateMapping for ColorMappedT<T, M>
 where
     T: DrawTarget,
 {
-    type Color = T::Color;
-    type Error = T::Error;
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
 
-    type DrawTarget<'a> = Scaled<'a, T> where Self: 'a;
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
+}
 
-    fn transform(&mut self) -> Self::DrawTarget<'_> {
-        self.0.scaled(self.1)
+transformer! {
+    pub struct MaskedT<T, M>(pub(crate) T, pub(crate) M)
+    where { M: PixelMask<T::Color> }
+    => for<'a> Masked<'a, T, M>,
+    |target, mask| target.masked(mask)
+}
+
+impl<T, M> CoordinateMapping for MaskedT<T, M>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
     }
 }
 
-impl<T> Flushable for Owned<ScaledT<T>>
+transformer! {
+    pub struct RoundClippedT<T, R>(pub(crate) T, pub(crate) R)
+    where { R: RowSpans }
+    => for<'a> RoundClipped<'a, T, &'a R>,
+    |target, spans| target.span_clipped(spans)
+}
+
+impl<T, R> CoordinateMapping for RoundClippedT<T, R>
 where
-    T: Flushable,
+    T: DrawTarget,
 {
-    fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0 .0.flush()
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
     }
 }
 
@@ -194,6 +457,48 @@ where
     }
 }
 
+impl<T, F> TransformerParams for FlushingT<T, F>
+where
+    T: DrawTarget + 'static,
+    F: FnMut(&mut T) -> Result<(), T::Error> + Send + Clone + 'static,
+{
+    type Inner = T;
+    type Params = F;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.0
+    }
+
+    fn inner_mut(&mut self) -> &mut Self::Inner {
+        &mut self.0
+    }
+
+    fn into_inner(self) -> Self::Inner {
+        self.0
+    }
+
+    fn params(&self) -> &Self::Params {
+        &self.1
+    }
+
+    fn params_mut(&mut self) -> &mut Self::Params {
+        &mut self.1
+    }
+}
+
+impl<T, F> CoordinateMapping for FlushingT<T, F>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(&self.0, point)
+    }
+}
+
 pub struct Owned<T>(pub(crate) T, pub(crate) Rectangle);
 
 impl<T> Owned<T>
@@ -207,6 +512,40 @@ where
     }
 }
 
+impl<T> Owned<T>
+where
+    T: TransformerParams,
+{
+    pub fn inner(&self) -> &T::Inner {
+        self.0.inner()
+    }
+
+    pub fn with_inner_mut<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut T::Inner) -> R,
+    {
+        let result = f(self.0.inner_mut());
+
+        self.1 = self.0.transform().bounding_box();
+
+        result
+    }
+
+    pub fn into_inner(self) -> T::Inner {
+        self.0.into_inner()
+    }
+
+    pub fn params(&self) -> &T::Params {
+        self.0.params()
+    }
+
+    pub fn set_params(&mut self, params: T::Params) {
+        *self.0.params_mut() = params;
+
+        self.1 = self.0.transform().bounding_box();
+    }
+}
+
 impl<T> DrawTarget for Owned<T>
 where
     T: Transformer,
@@ -246,12 +585,153 @@ where
     }
 }
 
+impl<T> CoordinateMapping for Owned<T>
+where
+    T: TransformerParams + CoordinateMapping,
+    T::Inner: DisplayMapping,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.0
+            .inner()
+            .map_from_display(point)
+            .and_then(|point| self.0.map_to_logical(point))
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.0
+            .map_to_physical(point)
+            .and_then(|point| self.0.inner().map_to_display(point))
+    }
+}
+
+impl<T> DisplayMapping for Owned<T>
+where
+    T: TransformerParams + CoordinateMapping,
+    T::Inner: DisplayMapping,
+{
+    fn map_from_display(&self, point: Point) -> Option<Point> {
+        self.map_to_logical(point)
+    }
+
+    fn map_to_display(&self, point: Point) -> Option<Point> {
+        self.map_to_physical(point)
+    }
+}
+
 impl<T> Flushable for Owned<T>
 where
-    T: Transformer,
-    for<'a> T::DrawTarget<'a>: Flushable,
+    T: FlushableTransformer,
 {
     fn flush(&mut self) -> Result<(), Self::Error> {
-        self.0.transform().flush()
+        self.0.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.0.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::draw_target::{Cropped, DrawTargetExt};
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use crate::draw_target::{CoordinateMapping, OwnedDrawTargetExt, RotateAngle, Transformer};
+
+    #[derive(Default)]
+    struct Screen([[bool; 16]; 8]);
+
+    impl Screen {
+        fn lit(&self) -> impl Iterator<Item = Point> + '_ {
+            self.bounding_box()
+                .points()
+                .filter(|point| self.0[point.y as usize][point.x as usize])
+        }
+    }
+
+    impl OriginDimensions for Screen {
+        fn size(&self) -> Size {
+            Size::new(16, 8)
+        }
+    }
+
+    impl DrawTarget for Screen {
+        type Color = BinaryColor;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.0[point.y as usize][point.x as usize] = color.is_on();
+            }
+
+            Ok(())
+        }
+    }
+
+    crate::transformer! {
+        struct InsetT<T>(T, u32) => for<'a> Cropped<'a, T>,
+        |target, inset| {
+            let area = target.bounding_box().offset(-(*inset as i32));
+
+            target.cropped(&area)
+        }
+    }
+
+    #[test]
+    fn maps_points_through_a_chain() {
+        let mut display = Screen::default()
+            .owned_rotated(RotateAngle::Degrees90)
+            .owned_cropped(&Rectangle::new(Point::new(6, 2), Size::new(6, 4)));
+
+        assert_eq!(display.bounding_box().size, Size::new(2, 4));
+
+        for angle in [RotateAngle::Degrees90, RotateAngle::Degrees180] {
+            display.with_inner_mut(|rotated| rotated.set_params(angle));
+
+            for point in display.bounding_box().points() {
+                let physical = display.map_to_physical(point).unwrap();
+
+                assert_eq!(display.map_to_logical(physical), Some(point));
+
+                display.draw_iter([Pixel(point, BinaryColor::On)]).unwrap();
+
+                assert!(display.inner().inner().lit().eq([physical]));
+
+                display.draw_iter([Pixel(point, BinaryColor::Off)]).unwrap();
+            }
+        }
+
+        // The crop is applied to the rotated display, which is 16x8 again after rotating by 180 degrees
+        assert_eq!(display.bounding_box().size, Size::new(6, 4));
+        assert_eq!(display.map_to_logical(Point::new(15, 7)), None);
+    }
+
+    #[test]
+    fn owns_a_macro_generated_transformation() {
+        let mut display = InsetT(Screen::default(), 2).into_owned();
+
+        assert_eq!(
+            display.bounding_box(),
+            Rectangle::new(Point::zero(), Size::new(12, 4))
+        );
+
+        display.set_params(3);
+
+        assert_eq!(display.params(), &3);
+        assert_eq!(display.bounding_box().size, Size::new(10, 2));
+
+        display
+            .draw_iter([Pixel(Point::new(9, 1), BinaryColor::On)])
+            .unwrap();
+
+        assert!(display.into_inner().lit().eq([Point::new(12, 4)]));
     }
 }
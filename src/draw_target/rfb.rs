@@ -0,0 +1,777 @@
+use core::cmp::{max, min};
+use core::marker::PhantomData;
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::string::String;
+use std::vec;
+use std::vec::Vec;
+
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::{
+    Dimensions, DrawTarget, OriginDimensions, PixelColor, Point, RgbColor, Size,
+};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::Flushable;
+
+const PROTOCOL_VERSION: &[u8; 12] = b"RFB 003.008\n";
+
+const SECURITY_NONE: u8 = 1;
+const SECURITY_FAILURE: &str = "Only the None security type is supported";
+
+const ENCODING_RAW: i32 = 0;
+const ENCODING_RRE: i32 = 2;
+
+pub struct RfbDisplay<C> {
+    listener: TcpListener,
+    name: String,
+    size: Size,
+    pixels: Vec<Rgb888>,
+    dirty: Dirty,
+    clients: Vec<Client>,
+    _color: PhantomData<C>,
+}
+
+impl<C> RfbDisplay<C>
+where
+    C: PixelColor + Into<Rgb888>,
+{
+    pub fn bind(addr: impl ToSocketAddrs, size: Size, name: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            name: name.into(),
+            size,
+            pixels: vec![Rgb888::BLACK; (size.width * size.height) as usize],
+            dirty: Dirty::new(size.height),
+            clients: Vec::new(),
+            _color: PhantomData,
+        })
+    }
+
+    pub fn listener(&self) -> &TcpListener {
+        &self.listener
+    }
+
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn poll(&mut self) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Ok(client) = Client::new(stream, self.size) {
+                        self.clients.push(client);
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        let size = self.size;
+        let name = &self.name;
+        let pixels = &self.pixels;
+
+        self.clients
+            .retain_mut(|client| client.serve(size, name, pixels).is_ok());
+
+        Ok(())
+    }
+
+    fn set(&mut self, point: Point, color: Rgb888) {
+        let offset = point.y as usize * self.size.width as usize + point.x as usize;
+
+        if self.pixels[offset] != color {
+            self.pixels[offset] = color;
+            self.dirty.mark(point.y, point.x, point.x);
+        }
+    }
+}
+
+impl<C> OriginDimensions for RfbDisplay<C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C> DrawTarget for RfbDisplay<C>
+where
+    C: PixelColor + Into<Rgb888>,
+{
+    type Error = io::Error;
+
+    type Color = C;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bbox = self.bounding_box();
+
+        for pixel in pixels {
+            if bbox.contains(pixel.0) {
+                self.set(pixel.0, pixel.1.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        let color = color.into();
+
+        for y in area.rows() {
+            for x in area.columns() {
+                self.set(Point::new(x, y), color);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> Flushable for RfbDisplay<C>
+where
+    C: PixelColor + Into<Rgb888>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        for client in &mut self.clients {
+            client.dirty.merge(&self.dirty);
+        }
+
+        self.dirty.clear();
+
+        self.poll()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    Version,
+    Security,
+    Init,
+    Ready,
+    Failed,
+}
+
+struct Client {
+    stream: TcpStream,
+    state: State,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    format: PixelFormat,
+    rre: bool,
+    request: Option<(bool, Rectangle)>,
+    dirty: Dirty,
+}
+
+impl Client {
+    fn new(stream: TcpStream, size: Size) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        let mut client = Self {
+            stream,
+            state: State::Version,
+            input: Vec::new(),
+            output: PROTOCOL_VERSION.to_vec(),
+            format: PixelFormat::DEFAULT,
+            rre: false,
+            request: None,
+            dirty: Dirty::new(size.height),
+        };
+
+        client.send()?;
+
+        Ok(client)
+    }
+
+    fn serve(&mut self, size: Size, name: &str, pixels: &[Rgb888]) -> io::Result<()> {
+        self.receive()?;
+
+        while self.process(size, name)? {}
+
+        if self.state == State::Failed {
+            self.send()?;
+
+            return Err(invalid("Unsupported security type"));
+        }
+
+        if self.state == State::Ready && self.output.is_empty() {
+            self.update(size, pixels);
+        }
+
+        self.send()
+    }
+
+    fn update(&mut self, size: Size, pixels: &[Rgb888]) {
+        let Some((incremental, area)) = self.request else {
+            return;
+        };
+
+        let bbox = Rectangle::new(Point::zero(), size);
+        let area = area.intersection(&bbox);
+
+        let rects = if incremental {
+            self.dirty
+                .rects(size.width)
+                .into_iter()
+                .map(|rect| rect.intersection(&area))
+                .filter(|rect| !rect.is_zero_sized())
+                .collect()
+        } else {
+            vec![area]
+        };
+
+        if incremental && rects.is_empty() {
+            return;
+        }
+
+        let mut update = vec![0, 0];
+        update.extend_from_slice(&(rects.len() as u16).to_be_bytes());
+
+        for rect in &rects {
+            self.write_rect(&mut update, rect, size, pixels);
+        }
+
+        self.output.extend_from_slice(&update);
+
+        self.request = None;
+        self.dirty.clear();
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        let mut buf = [0_u8; 256];
+
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(len) => self.input.extend_from_slice(&buf[..len]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn send(&mut self) -> io::Result<()> {
+        while !self.output.is_empty() {
+            match self.stream.write(&self.output) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(len) => {
+                    self.output.drain(..len);
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process(&mut self, size: Size, name: &str) -> io::Result<bool> {
+        let len = match self.state {
+            State::Version => 12,
+            State::Security | State::Init => 1,
+            State::Ready => return self.process_message(),
+            State::Failed => return Ok(false),
+        };
+
+        if self.input.len() < len {
+            return Ok(false);
+        }
+
+        match self.state {
+            State::Version => {
+                if &self.input[..4] != b"RFB " {
+                    return Err(invalid("Not an RFB client"));
+                }
+
+                self.output.extend_from_slice(&[1, SECURITY_NONE]);
+                self.state = State::Security;
+            }
+            State::Security => {
+                if self.input[0] == SECURITY_NONE {
+                    self.output.extend_from_slice(&0_u32.to_be_bytes());
+                    self.state = State::Init;
+                } else {
+                    let reason = SECURITY_FAILURE.as_bytes();
+
+                    self.output.extend_from_slice(&1_u32.to_be_bytes());
+                    self.output
+                        .extend_from_slice(&(reason.len() as u32).to_be_bytes());
+                    self.output.extend_from_slice(reason);
+                    self.state = State::Failed;
+                }
+            }
+            _ => {
+                self.output
+                    .extend_from_slice(&(size.width as u16).to_be_bytes());
+                self.output
+                    .extend_from_slice(&(size.height as u16).to_be_bytes());
+                self.format.write(&mut self.output);
+                self.output
+                    .extend_from_slice(&(name.len() as u32).to_be_bytes());
+                self.output.extend_from_slice(name.as_bytes());
+                self.state = State::Ready;
+            }
+        }
+
+        self.input.drain(..len);
+
+        Ok(true)
+    }
+
+    fn process_message(&mut self) -> io::Result<bool> {
+        let input = &self.input;
+
+        let Some(&message) = input.first() else {
+            return Ok(false);
+        };
+
+        let len = match message {
+            0 => 20,
+            2 if input.len() >= 4 => 4 + 4 * u16::from_be_bytes([input[2], input[3]]) as usize,
+            3 => 10,
+            4 => 8,
+            5 => 6,
+            6 if input.len() >= 8 => {
+                8 + u32::from_be_bytes([input[4], input[5], input[6], input[7]]) as usize
+            }
+            2 | 6 => return Ok(false),
+            _ => return Err(invalid("Unknown client message")),
+        };
+
+        if input.len() < len {
+            return Ok(false);
+        }
+
+        let msg = &input[..len];
+
+        match message {
+            0 => self.format = PixelFormat::read(&msg[4..])?,
+            2 => {
+                self.rre = msg[4..]
+                    .chunks_exact(4)
+                    .map(|encoding| i32::from_be_bytes(encoding.try_into().unwrap()))
+                    .find(|encoding| matches!(*encoding, ENCODING_RAW | ENCODING_RRE))
+                    == Some(ENCODING_RRE);
+            }
+            3 => {
+                let area = Rectangle::new(
+                    Point::new(
+                        u16::from_be_bytes([msg[2], msg[3]]) as _,
+                        u16::from_be_bytes([msg[4], msg[5]]) as _,
+                    ),
+                    Size::new(
+                        u16::from_be_bytes([msg[6], msg[7]]) as _,
+                        u16::from_be_bytes([msg[8], msg[9]]) as _,
+                    ),
+                );
+
+                let incremental = msg[1] != 0 && !matches!(self.request, Some((false, _)));
+
+                self.request = Some((incremental, area));
+            }
+            _ => (),
+        }
+
+        self.input.drain(..len);
+
+        Ok(true)
+    }
+
+    fn write_rect(&self, out: &mut Vec<u8>, rect: &Rectangle, size: Size, pixels: &[Rgb888]) {
+        let colors = rect.rows().flat_map(|y| {
+            rect.columns()
+                .map(move |x| pixels[y as usize * size.width as usize + x as usize])
+        });
+
+        out.extend_from_slice(&(rect.top_left.x as u16).to_be_bytes());
+        out.extend_from_slice(&(rect.top_left.y as u16).to_be_bytes());
+        out.extend_from_slice(&(rect.size.width as u16).to_be_bytes());
+        out.extend_from_slice(&(rect.size.height as u16).to_be_bytes());
+
+        if self.rre {
+            let mut counts = HashMap::new();
+            for color in colors.clone() {
+                *counts.entry(color).or_insert(0_usize) += 1;
+            }
+
+            let background = counts
+                .into_iter()
+                .max_by_key(|(_, count)| *count)
+                .map(|(color, _)| color)
+                .unwrap_or(Rgb888::BLACK);
+
+            let subrects = Self::subrects(rect, size, pixels, background);
+
+            let pixel_len = self.format.bytes_per_pixel();
+            let rre_len = 4 + pixel_len + subrects.len() * (pixel_len + 8);
+            let raw_len = (rect.size.width * rect.size.height) as usize * pixel_len;
+
+            if rre_len < raw_len {
+                out.extend_from_slice(&ENCODING_RRE.to_be_bytes());
+                out.extend_from_slice(&(subrects.len() as u32).to_be_bytes());
+                self.format.encode(background, out);
+
+                for (subrect, color) in subrects {
+                    self.format.encode(color, out);
+                    out.extend_from_slice(
+                        &((subrect.top_left.x - rect.top_left.x) as u16).to_be_bytes(),
+                    );
+                    out.extend_from_slice(
+                        &((subrect.top_left.y - rect.top_left.y) as u16).to_be_bytes(),
+                    );
+                    out.extend_from_slice(&(subrect.size.width as u16).to_be_bytes());
+                    out.extend_from_slice(&(subrect.size.height as u16).to_be_bytes());
+                }
+
+                return;
+            }
+        }
+
+        out.extend_from_slice(&ENCODING_RAW.to_be_bytes());
+
+        for color in colors {
+            self.format.encode(color, out);
+        }
+    }
+
+    fn subrects(
+        rect: &Rectangle,
+        size: Size,
+        pixels: &[Rgb888],
+        background: Rgb888,
+    ) -> Vec<(Rectangle, Rgb888)> {
+        let mut subrects = Vec::new();
+
+        for y in rect.rows() {
+            let row = &pixels[y as usize * size.width as usize..][..size.width as usize];
+
+            let mut run: Option<(i32, Rgb888)> = None;
+
+            for x in rect
+                .columns()
+                .chain(core::iter::once(rect.top_left.x + rect.size.width as i32))
+            {
+                let color = if rect.columns().contains(&x) {
+                    Some(row[x as usize])
+                } else {
+                    None
+                };
+
+                if let Some((start, run_color)) = run {
+                    if color != Some(run_color) {
+                        subrects.push((
+                            Rectangle::new(Point::new(start, y), Size::new((x - start) as _, 1)),
+                            run_color,
+                        ));
+
+                        run = None;
+                    }
+                }
+
+                if run.is_none() {
+                    run = color
+                        .filter(|color| *color != background)
+                        .map(|color| (x, color));
+                }
+            }
+        }
+
+        subrects
+    }
+}
+
+#[derive(Copy, Clone)]
+struct PixelFormat {
+    bits_per_pixel: u8,
+    depth: u8,
+    big_endian: bool,
+    max: [u16; 3],
+    shift: [u8; 3],
+}
+
+impl PixelFormat {
+    const DEFAULT: Self = Self {
+        bits_per_pixel: 32,
+        depth: 24,
+        big_endian: false,
+        max: [255, 255, 255],
+        shift: [16, 8, 0],
+    };
+
+    fn read(data: &[u8]) -> io::Result<Self> {
+        let format = Self {
+            bits_per_pixel: data[0],
+            depth: data[1],
+            big_endian: data[2] != 0,
+            max: [
+                u16::from_be_bytes([data[4], data[5]]),
+                u16::from_be_bytes([data[6], data[7]]),
+                u16::from_be_bytes([data[8], data[9]]),
+            ],
+            shift: [data[10], data[11], data[12]],
+        };
+
+        if data[3] == 0 || !matches!(format.bits_per_pixel, 8 | 16 | 32) {
+            Err(invalid("Unsupported pixel format"))
+        } else {
+            Ok(format)
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[self.bits_per_pixel, self.depth, self.big_endian as u8, 1]);
+
+        for max in self.max {
+            out.extend_from_slice(&max.to_be_bytes());
+        }
+
+        out.extend_from_slice(&self.shift);
+        out.extend_from_slice(&[0; 3]);
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        self.bits_per_pixel as usize / 8
+    }
+
+    fn encode(&self, color: Rgb888, out: &mut Vec<u8>) {
+        let value = [color.r(), color.g(), color.b()]
+            .into_iter()
+            .zip(self.max.into_iter().zip(self.shift))
+            .fold(0_u32, |value, (channel, (max, shift))| {
+                value | ((channel as u32 * max as u32 + 127) / 255) << shift
+            });
+
+        let len = self.bytes_per_pixel();
+
+        if self.big_endian {
+            out.extend_from_slice(&value.to_be_bytes()[4 - len..]);
+        } else {
+            out.extend_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+struct Dirty {
+    rows: Vec<Option<(i32, i32)>>,
+}
+
+impl Dirty {
+    fn new(height: u32) -> Self {
+        Self {
+            rows: vec![None; height as usize],
+        }
+    }
+
+    fn mark(&mut self, y: i32, x0: i32, x1: i32) {
+        let row = &mut self.rows[y as usize];
+
+        *row = Some(if let Some((start, end)) = *row {
+            (min(start, x0), max(end, x1))
+        } else {
+            (x0, x1)
+        });
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (y, row) in other.rows.iter().enumerate() {
+            if let Some((x0, x1)) = *row {
+                self.mark(y as _, x0, x1);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.rows.fill(None);
+    }
+
+    fn rects(&self, width: u32) -> Vec<Rectangle> {
+        let mut rects = Vec::new();
+        let mut band: Option<(i32, i32, i32)> = None;
+
+        for (y, row) in self
+            .rows
+            .iter()
+            .copied()
+            .chain(core::iter::once(None))
+            .enumerate()
+        {
+            match (band, row) {
+                (Some((top, x0, x1)), Some((start, end))) => {
+                    band = Some((top, min(x0, start), max(x1, end)))
+                }
+                (None, Some((start, end))) => band = Some((y as _, start, end)),
+                (Some((top, x0, x1)), None) => {
+                    rects.push(Rectangle::new(
+                        Point::new(x0, top),
+                        Size::new(min((x1 - x0 + 1) as u32, width), (y as i32 - top) as _),
+                    ));
+
+                    band = None;
+                }
+                (None, None) => (),
+            }
+        }
+
+        rects
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read, Write};
+    use std::net::TcpStream;
+    use std::time::Duration;
+    use std::vec;
+    use std::vec::Vec;
+
+    use embedded_graphics::pixelcolor::Rgb888;
+    use embedded_graphics::prelude::{DrawTarget, Point, RgbColor, Size};
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{Flushable, RfbDisplay, PROTOCOL_VERSION, SECURITY_FAILURE};
+
+    fn connect() -> (RfbDisplay<Rgb888>, TcpStream) {
+        let display = RfbDisplay::bind("127.0.0.1:0", Size::new(8, 2), "test").unwrap();
+
+        let stream = TcpStream::connect(display.listener().local_addr().unwrap()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+
+        (display, stream)
+    }
+
+    fn read(display: &mut RfbDisplay<Rgb888>, stream: &mut TcpStream, len: usize) -> Vec<u8> {
+        let mut data = vec![0; len];
+        let mut read = 0;
+
+        for _ in 0..500 {
+            display.poll().unwrap();
+
+            match stream.read(&mut data[read..]) {
+                Ok(0) => break,
+                Ok(count) => read += count,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                Err(err) => panic!("{err}"),
+            }
+
+            if read == len {
+                return data;
+            }
+        }
+
+        panic!("Expected {len} bytes, got {read}");
+    }
+
+    fn handshake(display: &mut RfbDisplay<Rgb888>, stream: &mut TcpStream) {
+        assert_eq!(read(display, stream, 12), PROTOCOL_VERSION);
+        stream.write_all(PROTOCOL_VERSION).unwrap();
+
+        assert_eq!(read(display, stream, 2), [1, 1]);
+        stream.write_all(&[1]).unwrap();
+
+        assert_eq!(read(display, stream, 4), [0, 0, 0, 0]);
+        stream.write_all(&[1]).unwrap();
+
+        let init = read(display, stream, 2 + 2 + 16 + 4 + 4);
+        assert_eq!(init[..4], [0, 8, 0, 2]);
+        assert_eq!(init[4..8], [32, 24, 0, 1]);
+        assert_eq!(init[20..], [0, 0, 0, 4, b't', b'e', b's', b't']);
+
+        assert_eq!(display.clients(), 1);
+    }
+
+    fn request(stream: &mut TcpStream, incremental: bool) {
+        stream
+            .write_all(&[3, incremental as u8, 0, 0, 0, 0, 0, 8, 0, 2])
+            .unwrap();
+    }
+
+    #[test]
+    fn updates() {
+        let (mut display, mut stream) = connect();
+
+        handshake(&mut display, &mut stream);
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(1, 0), Size::new(2, 1)),
+                Rgb888::RED,
+            )
+            .unwrap();
+        display.flush().unwrap();
+
+        request(&mut stream, false);
+
+        let update = read(&mut display, &mut stream, 4 + 12 + 16 * 4);
+        assert_eq!(update[..4], [0, 0, 0, 1]);
+        assert_eq!(update[4..16], [0, 0, 0, 0, 0, 8, 0, 2, 0, 0, 0, 0]);
+
+        let pixels: Vec<_> = update[16..].chunks(4).collect();
+        assert_eq!(pixels[0], [0, 0, 0, 0]);
+        assert_eq!(pixels[1], [0, 0, 255, 0]);
+        assert_eq!(pixels[2], [0, 0, 255, 0]);
+        assert!(pixels[3..].iter().all(|pixel| *pixel == [0, 0, 0, 0]));
+
+        stream.write_all(&[2, 0, 0, 1, 0, 0, 0, 2]).unwrap();
+
+        display
+            .fill_solid(
+                &Rectangle::new(Point::new(0, 1), Size::new(8, 1)),
+                Rgb888::GREEN,
+            )
+            .unwrap();
+        display
+            .draw_iter([Pixel(Point::new(2, 1), Rgb888::WHITE)])
+            .unwrap();
+        display.flush().unwrap();
+
+        request(&mut stream, true);
+
+        let update = read(&mut display, &mut stream, 4 + 12 + 4 + 4 + 4 + 8);
+        assert_eq!(update[..4], [0, 0, 0, 1]);
+        assert_eq!(update[4..16], [0, 0, 0, 1, 0, 8, 0, 1, 0, 0, 0, 2]);
+        assert_eq!(update[16..20], [0, 0, 0, 1]);
+        assert_eq!(update[20..24], [0, 255, 0, 0]);
+        assert_eq!(update[24..28], [255, 255, 255, 0]);
+        assert_eq!(update[28..], [0, 2, 0, 0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn security_failure() {
+        let (mut display, mut stream) = connect();
+
+        assert_eq!(read(&mut display, &mut stream, 12), PROTOCOL_VERSION);
+        stream.write_all(PROTOCOL_VERSION).unwrap();
+
+        assert_eq!(read(&mut display, &mut stream, 2), [1, 1]);
+        stream.write_all(&[2]).unwrap();
+
+        let result = read(&mut display, &mut stream, 8 + SECURITY_FAILURE.len());
+        assert_eq!(result[..4], [0, 0, 0, 1]);
+        assert_eq!(result[4..8], (SECURITY_FAILURE.len() as u32).to_be_bytes());
+        assert_eq!(&result[8..], SECURITY_FAILURE.as_bytes());
+
+        assert_eq!(display.clients(), 0);
+    }
+}
@@ -0,0 +1,254 @@
+use core::marker::PhantomData;
+
+use embedded_graphics::prelude::{Dimensions, DrawTarget, IntoStorage, PixelColor, Point};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{PackedFramebuffer, ReferenceBuffer};
+
+pub struct RleFramebuffer<'a, COLOR> {
+    buf: &'a mut [u8],
+    len: Option<usize>,
+    _color: PhantomData<COLOR>,
+}
+
+impl<'a, COLOR> RleFramebuffer<'a, COLOR>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            len: None,
+            _color: PhantomData,
+        }
+    }
+
+    pub fn compressed_len(&self) -> Option<usize> {
+        self.len
+    }
+
+    pub fn into_bytes(self) -> &'a mut [u8] {
+        self.buf
+    }
+
+    fn compress(&mut self, current: &PackedFramebuffer<'_, COLOR>) {
+        let mut len = 0;
+
+        // A framebuffer narrower than a byte has no bytes at all, but `chunks` panics on 0
+        for row in current.bytes().chunks(current.bytes_per_row().max(1)) {
+            let mut bytes = row.iter().peekable();
+
+            while let Some(byte) = bytes.next() {
+                let mut count = 1_u8;
+                while count < u8::MAX && bytes.next_if_eq(&byte).is_some() {
+                    count += 1;
+                }
+
+                if len + 2 > self.buf.len() {
+                    warn!(
+                        "Compressed frame does not fit in the {} bytes RLE buffer, the next flush resends it in full",
+                        self.buf.len()
+                    );
+
+                    self.len = None;
+
+                    return;
+                }
+
+                self.buf[len] = count;
+                self.buf[len + 1] = *byte;
+                len += 2;
+            }
+        }
+
+        self.len = Some(len);
+    }
+
+    fn decompress(&self) -> impl Iterator<Item = u8> + '_ {
+        self.buf[..self.len.unwrap_or(0)]
+            .chunks_exact(2)
+            .flat_map(|run| core::iter::repeat(run[1]).take(run[0] as _))
+    }
+}
+
+impl<'a, COLOR> ReferenceBuffer for RleFramebuffer<'a, COLOR>
+where
+    COLOR: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Color = COLOR;
+
+    fn apply<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let changes = self.diff_area(current, &current.bounding_box(), to)?;
+
+        self.compress(current);
+
+        trace!(
+            "Display updated ({} changed pixels, {:?} bytes compressed reference)",
+            changes,
+            self.len
+        );
+
+        Ok(changes)
+    }
+
+    fn diff_area<D>(
+        &mut self,
+        current: &PackedFramebuffer<'_, Self::Color>,
+        area: &Rectangle,
+        to: &mut D,
+    ) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let pixels_per_byte = PackedFramebuffer::<COLOR>::PIXELS_PER_BYTE;
+        let bytes_per_row = current.bytes_per_row();
+
+        let valid = self.len.is_some();
+        let reference = self.decompress().map(Some).chain(core::iter::repeat(None));
+
+        let mut changes = 0_usize;
+
+        let pixels = current
+            .bytes()
+            .iter()
+            .zip(reference)
+            .enumerate()
+            .filter(|(_, (byte, old))| !valid || Some(**byte) != *old)
+            .flat_map(|(offset, (byte, old))| {
+                let y = (offset / bytes_per_row) as i32;
+                let x = ((offset % bytes_per_row) * pixels_per_byte) as i32;
+
+                (0..pixels_per_byte).filter_map(move |index| {
+                    let color = PackedFramebuffer::<COLOR>::pixel_in_byte(*byte, index);
+
+                    let changed = old.map_or(true, |old| {
+                        PackedFramebuffer::<COLOR>::pixel_in_byte(old, index) != color
+                    });
+
+                    changed.then_some(Pixel(Point::new(x + index as i32, y), color))
+                })
+            })
+            .filter(|pixel| area.contains(pixel.0))
+            .inspect(|_| changes += 1);
+
+        to.draw_iter(pixels)?;
+
+        Ok(changes)
+    }
+
+    fn sync(&mut self, current: &PackedFramebuffer<'_, Self::Color>) {
+        self.compress(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::raw::RawU2;
+    use embedded_graphics::prelude::{DrawTarget, PixelColor, Point, Size};
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::RleFramebuffer;
+    use crate::draw_target::{PackedFramebuffer, ReferenceBuffer};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl PixelColor for Gray {
+        type Raw = RawU2;
+    }
+
+    impl From<Gray> for RawU2 {
+        fn from(color: Gray) -> Self {
+            RawU2::new(color.0)
+        }
+    }
+
+    impl From<u8> for Gray {
+        fn from(bits: u8) -> Self {
+            Gray(bits)
+        }
+    }
+
+    fn draw(fb: &mut PackedFramebuffer<'_, Gray>, x: i32, y: i32, color: u8) {
+        fb.draw_iter([Pixel(Point::new(x, y), Gray(color))])
+            .unwrap();
+    }
+
+    #[test]
+    fn round_trips_through_the_compressed_reference() {
+        let mut buf = [0; 6];
+        let mut current = PackedFramebuffer::<Gray>::new(&mut buf, 8, 3);
+        let mut display_buf = [0xff; 6];
+        let mut display = PackedFramebuffer::<Gray>::new(&mut display_buf, 8, 3);
+        let mut rle_buf = [0; 12];
+        let mut rle = RleFramebuffer::<Gray>::new(&mut rle_buf);
+
+        draw(&mut current, 1, 0, 3);
+        draw(&mut current, 6, 2, 2);
+
+        // Without a reference, the whole frame is sent
+        assert_eq!(rle.apply(&current, &mut display), Ok(24));
+        assert_eq!(display.bytes(), current.bytes());
+        // Two runs in rows 0 and 2, one in row 1
+        assert_eq!(rle.compressed_len(), Some(10));
+
+        draw(&mut current, 2, 1, 1);
+        draw(&mut current, 7, 1, 3);
+
+        // A diff outside of the changes sends nothing and leaves the reference alone
+        let left = Rectangle::new(Point::zero(), Size::new(2, 3));
+        assert_eq!(rle.diff_area(&current, &left, &mut display), Ok(0));
+
+        let right = Rectangle::new(Point::new(4, 0), Size::new(4, 3));
+        assert_eq!(rle.diff_area(&current, &right, &mut display), Ok(1));
+        assert_eq!(rle.apply(&current, &mut display), Ok(2));
+        assert_eq!(display.bytes(), current.bytes());
+
+        assert_eq!(rle.apply(&current, &mut display), Ok(0));
+    }
+
+    #[test]
+    fn resends_the_frame_when_it_does_not_fit() {
+        let mut buf = [0; 6];
+        let mut current = PackedFramebuffer::<Gray>::new(&mut buf, 8, 3);
+        let mut display_buf = [0; 6];
+        let mut display = PackedFramebuffer::<Gray>::new(&mut display_buf, 8, 3);
+        let mut rle_buf = [0; 8];
+        let mut rle = RleFramebuffer::<Gray>::new(&mut rle_buf);
+
+        rle.sync(&current);
+        assert_eq!(rle.compressed_len(), Some(6));
+
+        // 6 runs of 2 bytes each do not fit in 8 bytes
+        for y in 0..3 {
+            draw(&mut current, 0, y, 1);
+            draw(&mut current, 4, y, 2);
+        }
+
+        assert_eq!(rle.apply(&current, &mut display), Ok(6));
+        assert_eq!(rle.compressed_len(), None);
+        assert_eq!(display.bytes(), current.bytes());
+
+        // Every flush resends the frame in full until it fits again
+        assert_eq!(rle.apply(&current, &mut display), Ok(24));
+
+        for y in 0..3 {
+            draw(&mut current, 0, y, 0);
+            draw(&mut current, 4, y, 0);
+        }
+
+        assert_eq!(rle.apply(&current, &mut display), Ok(24));
+        assert_eq!(rle.compressed_len(), Some(6));
+        assert_eq!(display.bytes(), current.bytes());
+        assert_eq!(rle.apply(&current, &mut display), Ok(0));
+    }
+}
@@ -4,7 +4,10 @@ use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Point, PointsIter
 use embedded_graphics::primitives::Rectangle;
 use embedded_graphics::Pixel;
 
+use super::{CoordinateMapping, Flushable, Refresh};
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum RotateAngle {
     Degrees90,
     Degrees180,
@@ -15,21 +18,49 @@ impl RotateAngle {
     fn transform(&self, point: Point, pdim: &Rectangle) -> Point {
         match self {
             RotateAngle::Degrees90 => Point::new(
-                pdim.top_left.x + pdim.size.width as i32 - point.y,
+                pdim.top_left.x + pdim.size.width as i32 - 1 - point.y,
                 pdim.top_left.y + point.x,
             ),
             RotateAngle::Degrees180 => Point::new(
-                pdim.top_left.x + pdim.size.height as i32 - point.x,
-                pdim.top_left.y + pdim.size.width as i32 - point.y,
+                pdim.top_left.x + pdim.size.width as i32 - 1 - point.x,
+                pdim.top_left.y + pdim.size.height as i32 - 1 - point.y,
             ),
             RotateAngle::Degrees270 => Point::new(
                 pdim.top_left.x + point.y,
-                pdim.top_left.y + pdim.size.height as i32 - point.x,
+                pdim.top_left.y + pdim.size.height as i32 - 1 - point.x,
             ),
         }
     }
 
-    fn transform_size(&self, size: Size) -> Size {
+    fn inverse_transform(&self, point: Point, pdim: &Rectangle) -> Point {
+        match self {
+            RotateAngle::Degrees90 => Point::new(
+                point.y - pdim.top_left.y,
+                pdim.top_left.x + pdim.size.width as i32 - 1 - point.x,
+            ),
+            RotateAngle::Degrees180 => Point::new(
+                pdim.top_left.x + pdim.size.width as i32 - 1 - point.x,
+                pdim.top_left.y + pdim.size.height as i32 - 1 - point.y,
+            ),
+            RotateAngle::Degrees270 => Point::new(
+                pdim.top_left.y + pdim.size.height as i32 - 1 - point.y,
+                point.x - pdim.top_left.x,
+            ),
+        }
+    }
+
+    pub(crate) fn map_to_logical(&self, point: Point, pdim: &Rectangle) -> Option<Point> {
+        pdim.contains(point)
+            .then(|| self.inverse_transform(point, pdim))
+    }
+
+    pub(crate) fn map_to_physical(&self, point: Point, pdim: &Rectangle) -> Option<Point> {
+        Rectangle::new(Point::zero(), self.transform_size(pdim.size))
+            .contains(point)
+            .then(|| self.transform(point, pdim))
+    }
+
+    pub(crate) fn transform_size(&self, size: Size) -> Size {
         if *self != RotateAngle::Degrees180 {
             Size::new(size.height, size.width)
         } else {
@@ -38,16 +69,20 @@ impl RotateAngle {
     }
 
     fn transform_rect(&self, rect: &Rectangle, pdim: &Rectangle) -> Rectangle {
-        let point1 = self.transform(rect.top_left, pdim);
-        let point2 = self.transform(rect.top_left + rect.size, pdim);
+        if let Some(bottom_right) = rect.bottom_right() {
+            let point1 = self.transform(rect.top_left, pdim);
+            let point2 = self.transform(bottom_right, pdim);
 
-        let x1 = min(point1.x, point2.x);
-        let y1 = min(point1.y, point2.y);
+            let x1 = min(point1.x, point2.x);
+            let y1 = min(point1.y, point2.y);
 
-        let x2 = max(point1.x, point2.x);
-        let y2 = max(point1.y, point2.y);
+            let x2 = max(point1.x, point2.x);
+            let y2 = max(point1.y, point2.y);
 
-        Rectangle::with_corners(Point::new(x1, y1), Point::new(x2, y2))
+            Rectangle::with_corners(Point::new(x1, y1), Point::new(x2, y2))
+        } else {
+            Rectangle::new(self.transform(rect.top_left, pdim), Size::zero())
+        }
     }
 }
 
@@ -116,6 +151,21 @@ where
     }
 }
 
+impl<'a, T> CoordinateMapping for Rotated<'a, T>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.angle
+            .map_to_logical(point, &self.parent.bounding_box())
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.angle
+            .map_to_physical(point, &self.parent.bounding_box())
+    }
+}
+
 impl<'a, T> OriginDimensions for Rotated<'a, T>
 where
     T: DrawTarget,
@@ -126,3 +176,131 @@ where
         self.angle.transform_size(bbox.size)
     }
 }
+
+impl<'a, T> Flushable for Rotated<'a, T>
+where
+    T: Flushable,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_graphics::pixelcolor::raw::RawU16;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, RawData, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{CoordinateMapping, RotateAngle, Rotated};
+
+    #[derive(Default)]
+    struct Grid {
+        pixels: [[Option<u16>; 3]; 2],
+    }
+
+    impl OriginDimensions for Grid {
+        fn size(&self) -> Size {
+            Size::new(3, 2)
+        }
+    }
+
+    impl DrawTarget for Grid {
+        type Error = Infallible;
+
+        type Color = Rgb565;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                assert!(
+                    self.bounding_box().contains(point),
+                    "{point:?} is outside of the parent"
+                );
+
+                self.pixels[point.y as usize][point.x as usize] =
+                    Some(RawU16::from(color).into_inner());
+            }
+
+            Ok(())
+        }
+    }
+
+    fn color(index: u16) -> Rgb565 {
+        Rgb565::from(RawU16::new(index))
+    }
+
+    fn fill(angle: RotateAngle) -> [[Option<u16>; 3]; 2] {
+        let mut grid = Grid::default();
+        let mut rotated = Rotated::new(&mut grid, angle);
+
+        let area = rotated.bounding_box();
+        rotated.fill_contiguous(&area, (0..).map(color)).unwrap();
+
+        grid.pixels
+    }
+
+    #[test]
+    fn maps_every_pixel_inside_the_parent() {
+        assert_eq!(
+            fill(RotateAngle::Degrees90),
+            [[Some(4), Some(2), Some(0)], [Some(5), Some(3), Some(1)]]
+        );
+        assert_eq!(
+            fill(RotateAngle::Degrees180),
+            [[Some(5), Some(4), Some(3)], [Some(2), Some(1), Some(0)]]
+        );
+        assert_eq!(
+            fill(RotateAngle::Degrees270),
+            [[Some(1), Some(3), Some(5)], [Some(0), Some(2), Some(4)]]
+        );
+    }
+
+    #[test]
+    fn fills_the_rotated_rectangle() {
+        let mut grid = Grid::default();
+
+        Rotated::new(&mut grid, RotateAngle::Degrees90)
+            .fill_solid(&Rectangle::new(Point::new(0, 1), Size::new(2, 2)), color(7))
+            .unwrap();
+
+        assert_eq!(
+            grid.pixels,
+            [[Some(7), Some(7), None], [Some(7), Some(7), None]]
+        );
+    }
+
+    #[test]
+    fn mapping_matches_drawing() {
+        for angle in [
+            RotateAngle::Degrees90,
+            RotateAngle::Degrees180,
+            RotateAngle::Degrees270,
+        ] {
+            let mut grid = Grid::default();
+            let mut rotated = Rotated::new(&mut grid, angle);
+
+            for point in rotated.bounding_box().points() {
+                let physical = rotated.map_to_physical(point).unwrap();
+
+                assert_eq!(rotated.map_to_logical(physical), Some(point));
+
+                rotated.draw_iter([Pixel(point, color(1))]).unwrap();
+            }
+
+            assert!(grid.pixels.iter().flatten().all(|pixel| pixel.is_some()));
+        }
+    }
+}
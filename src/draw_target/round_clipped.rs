@@ -0,0 +1,412 @@
+use embedded_graphics::prelude::{Dimensions, DrawTarget, Point, Size};
+use embedded_graphics::primitives::{Circle, ContainsPoint, Rectangle};
+use embedded_graphics::Pixel;
+
+use super::{identity_mapping, CoordinateMapping, Flushable, Refresh};
+
+pub trait RowSpans {
+    fn clip_row(&self, row: &Rectangle) -> Rectangle;
+
+    fn is_visible(&self, point: Point) -> bool {
+        !self
+            .clip_row(&Rectangle::new(point, Size::new(1, 1)))
+            .is_zero_sized()
+    }
+
+    fn is_fully_visible(&self, area: &Rectangle) -> bool {
+        area.rows().all(|y| {
+            let row = row(area, y);
+
+            self.clip_row(&row) == row
+        })
+    }
+}
+
+impl RowSpans for () {
+    fn clip_row(&self, row: &Rectangle) -> Rectangle {
+        *row
+    }
+
+    fn is_visible(&self, _point: Point) -> bool {
+        true
+    }
+
+    fn is_fully_visible(&self, _area: &Rectangle) -> bool {
+        true
+    }
+}
+
+impl<R> RowSpans for &R
+where
+    R: RowSpans + ?Sized,
+{
+    fn clip_row(&self, row: &Rectangle) -> Rectangle {
+        (**self).clip_row(row)
+    }
+
+    fn is_visible(&self, point: Point) -> bool {
+        (**self).is_visible(point)
+    }
+
+    fn is_fully_visible(&self, area: &Rectangle) -> bool {
+        (**self).is_fully_visible(area)
+    }
+}
+
+impl RowSpans for Circle {
+    fn clip_row(&self, row: &Rectangle) -> Rectangle {
+        let radius = self.diameter.saturating_sub(1) as i64;
+        let center_2x = self.top_left * 2 + Size::new(radius as _, radius as _);
+
+        let threshold = if self.diameter <= 4 {
+            self.diameter.pow(2) - self.diameter / 2
+        } else {
+            self.diameter.pow(2)
+        } as i64;
+
+        let dy = (center_2x.y - row.top_left.y * 2) as i64;
+        let remaining = threshold - dy * dy;
+
+        if remaining <= 0 {
+            return Rectangle::zero();
+        }
+
+        let mut dx = isqrt((remaining - 1) as u64) as i64;
+        if (dx - center_2x.x as i64) % 2 != 0 {
+            dx -= 1;
+        }
+
+        if dx < 0 {
+            return Rectangle::zero();
+        }
+
+        Rectangle::new(
+            Point::new(((center_2x.x as i64 - dx) / 2) as _, row.top_left.y),
+            Size::new(dx as u32 + 1, 1),
+        )
+        .intersection(row)
+    }
+
+    fn is_visible(&self, point: Point) -> bool {
+        self.contains(point)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Span {
+    pub start: i32,
+    pub width: u32,
+}
+
+impl Span {
+    pub const fn new(start: i32, width: u32) -> Self {
+        Self { start, width }
+    }
+}
+
+impl RowSpans for [Span] {
+    fn clip_row(&self, row: &Rectangle) -> Rectangle {
+        let span = usize::try_from(row.top_left.y)
+            .ok()
+            .and_then(|y| self.get(y));
+
+        if let Some(span) = span {
+            Rectangle::new(
+                Point::new(span.start, row.top_left.y),
+                Size::new(span.width, 1),
+            )
+            .intersection(row)
+        } else {
+            Rectangle::zero()
+        }
+    }
+}
+
+impl<const N: usize> RowSpans for [Span; N] {
+    fn clip_row(&self, row: &Rectangle) -> Rectangle {
+        self[..].clip_row(row)
+    }
+}
+
+pub(crate) fn inscribed_circle(area: &Rectangle) -> Circle {
+    let diameter = area.size.width.min(area.size.height);
+
+    Circle::with_center(area.center(), diameter)
+}
+
+pub struct RoundClipped<'a, T, R>
+where
+    T: DrawTarget,
+{
+    parent: &'a mut T,
+    spans: R,
+}
+
+impl<'a, T, R> RoundClipped<'a, T, R>
+where
+    T: DrawTarget,
+    R: RowSpans,
+{
+    pub(crate) fn new(parent: &'a mut T, spans: R) -> Self {
+        Self { parent, spans }
+    }
+
+    pub fn spans(&self) -> &R {
+        &self.spans
+    }
+}
+
+impl<'a, T> RoundClipped<'a, T, Circle>
+where
+    T: DrawTarget,
+{
+    pub(crate) fn inscribed(parent: &'a mut T) -> Self {
+        let circle = inscribed_circle(&parent.bounding_box());
+
+        Self::new(parent, circle)
+    }
+}
+
+impl<'a, T, R> DrawTarget for RoundClipped<'a, T, R>
+where
+    T: DrawTarget,
+    R: RowSpans,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let spans = &self.spans;
+
+        self.parent
+            .draw_iter(pixels.into_iter().filter(|pixel| spans.is_visible(pixel.0)))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.spans.is_fully_visible(area) {
+            return self.parent.fill_contiguous(area, colors);
+        }
+
+        let mut colors = colors.into_iter();
+
+        for y in area.rows() {
+            let row = row(area, y);
+            let visible = self.spans.clip_row(&row);
+
+            if visible.is_zero_sized() {
+                colors.by_ref().take(row.size.width as _).for_each(drop);
+            } else {
+                let before = (visible.top_left.x - row.top_left.x) as usize;
+                let after = (row.size.width - visible.size.width) as usize - before;
+
+                colors.by_ref().take(before).for_each(drop);
+
+                let mut visible_colors = colors.by_ref().take(visible.size.width as _);
+                self.parent.fill_contiguous(&visible, &mut visible_colors)?;
+                visible_colors.for_each(drop);
+
+                colors.by_ref().take(after).for_each(drop);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if self.spans.is_fully_visible(area) {
+            return self.parent.fill_solid(area, color);
+        }
+
+        for y in area.rows() {
+            let visible = self.spans.clip_row(&row(area, y));
+
+            if !visible.is_zero_sized() {
+                self.parent.fill_solid(&visible, color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T, R> Dimensions for RoundClipped<'a, T, R>
+where
+    T: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.parent.bounding_box()
+    }
+}
+
+impl<'a, T, R> CoordinateMapping for RoundClipped<'a, T, R>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        identity_mapping(self, point)
+    }
+}
+
+impl<'a, T, R> Flushable for RoundClipped<'a, T, R>
+where
+    T: Flushable,
+    R: RowSpans,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+fn row(area: &Rectangle, y: i32) -> Rectangle {
+    Rectangle::new(
+        Point::new(area.top_left.x, y),
+        Size::new(area.size.width, 1),
+    )
+}
+
+fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+
+    let mut root = value;
+    let mut next = (root + 1) / 2;
+
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::convert::Infallible;
+
+    use embedded_graphics::pixelcolor::raw::RawU16;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, RawData, Size,
+    };
+    use embedded_graphics::primitives::{Circle, ContainsPoint, Rectangle};
+    use embedded_graphics::Pixel;
+
+    use super::{RoundClipped, RowSpans};
+
+    #[test]
+    fn clip_row_matches_circle_contains() {
+        for diameter in (1..=10).chain([240]) {
+            for top_left in [Point::new(0, 0), Point::new(3, -2), Point::new(-7, 5)] {
+                let circle = Circle::new(top_left, diameter);
+                let bbox = circle.bounding_box();
+
+                for y in bbox.top_left.y - 1..=bbox.top_left.y + diameter as i32 {
+                    let row = Rectangle::new(
+                        Point::new(bbox.top_left.x - 2, y),
+                        Size::new(diameter + 4, 1),
+                    );
+
+                    let clipped = circle.clip_row(&row);
+
+                    for x in row.columns() {
+                        let point = Point::new(x, y);
+
+                        assert_eq!(
+                            clipped.contains(point),
+                            circle.contains(point),
+                            "diameter {diameter}, top left {top_left:?}, point {point:?}",
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    struct Grid {
+        pixels: [[Option<u16>; 16]; 16],
+    }
+
+    impl OriginDimensions for Grid {
+        fn size(&self) -> Size {
+            Size::new(16, 16)
+        }
+    }
+
+    impl DrawTarget for Grid {
+        type Error = Infallible;
+
+        type Color = Rgb565;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                if self.bounding_box().contains(point) {
+                    self.pixels[point.y as usize][point.x as usize] =
+                        Some(RawU16::from(color).into_inner());
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_consumes_all_colors() {
+        let circle = Circle::new(Point::zero(), 16);
+
+        for area in [
+            Rectangle::new(Point::new(-2, -1), Size::new(20, 10)),
+            Rectangle::new(Point::new(0, 0), Size::new(3, 3)),
+            Rectangle::new(Point::new(4, 4), Size::new(8, 8)),
+            Rectangle::new(Point::new(5, 14), Size::new(6, 5)),
+        ] {
+            let mut grid = Grid {
+                pixels: [[None; 16]; 16],
+            };
+
+            let consumed = Cell::new(0);
+            let len = (area.size.width * area.size.height) as usize;
+
+            RoundClipped::new(&mut grid, circle)
+                .fill_contiguous(
+                    &area,
+                    (0..len as u16 + 5)
+                        .map(|index| Rgb565::from(RawU16::new(index)))
+                        .inspect(|_| consumed.set(consumed.get() + 1)),
+                )
+                .unwrap();
+
+            assert_eq!(consumed.get(), len, "{area:?}");
+
+            for (index, point) in area.points().enumerate() {
+                if grid.bounding_box().contains(point) {
+                    let expected = circle.contains(point).then_some(index as u16);
+
+                    assert_eq!(
+                        grid.pixels[point.y as usize][point.x as usize], expected,
+                        "{area:?} {point:?}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@ use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Point, PointsIter
 use embedded_graphics::primitives::Rectangle;
 use embedded_graphics::Pixel;
 
+use super::{AffineTransform, CoordinateMapping, Flushable, Refresh};
+
 pub struct Scaled<'a, T>
 where
     T: DrawTarget,
@@ -18,11 +20,21 @@ where
         Self { parent, size }
     }
 
-    fn transform(point: Point, size: Size, pdim: &Rectangle) -> Point {
-        Point::new(
-            pdim.top_left.x + point.x * size.width as i32 / pdim.size.width as i32,
-            pdim.top_left.y + point.y * size.height as i32 / pdim.size.height as i32,
-        )
+    fn transform(&self) -> AffineTransform {
+        AffineTransform::new(self.parent.bounding_box()).scale(self.size)
+    }
+}
+
+impl<'a, T> CoordinateMapping for Scaled<'a, T>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.transform().map_to_logical(point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.transform().map_to_physical(point)
     }
 }
 
@@ -37,32 +49,49 @@ where
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        let pdim = self.parent.bounding_box();
-        let size = self.size;
+        let transform = self.transform();
 
-        self.parent.draw_iter(
-            pixels
-                .into_iter()
-                .map(|pixel| Pixel(Self::transform(pixel.0, size, &pdim), pixel.1)),
-        )
+        if transform.upscales() {
+            for Pixel(point, color) in pixels {
+                self.parent
+                    .fill_solid(&transform.transform_pixel(point), color)?;
+            }
+
+            return Ok(());
+        }
+
+        self.parent
+            .draw_iter(pixels.into_iter().filter_map(|pixel| {
+                transform
+                    .transform_point(pixel.0)
+                    .map(|point| Pixel(point, pixel.1))
+            }))
     }
 
     fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Self::Color>,
     {
-        let pdim = self.parent.bounding_box();
-        let size = self.size;
+        let transform = self.transform();
+
+        if transform.upscales() {
+            for (point, color) in area.points().zip(colors) {
+                self.parent
+                    .fill_solid(&transform.transform_pixel(point), color)?;
+            }
+
+            return Ok(());
+        }
 
         self.parent.draw_iter(
-            area.points()
-                .zip(colors)
-                .map(|(pos, color)| Pixel(Self::transform(pos, size, &pdim), color)),
+            area.points().zip(colors).filter_map(|(pos, color)| {
+                transform.transform_point(pos).map(|pos| Pixel(pos, color))
+            }),
         )
     }
 
     fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
-        let area = Rectangle::new(area.top_left, self.size);
+        let area = self.transform().transform_rect(area);
 
         self.parent.fill_solid(&area, color)
     }
@@ -80,3 +109,126 @@ where
         self.size
     }
 }
+
+impl<'a, T> Flushable for Scaled<'a, T>
+where
+    T: Flushable,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{CoordinateMapping, Scaled};
+
+    #[derive(Default)]
+    struct Grid {
+        pixels: [[bool; 4]; 2],
+    }
+
+    impl OriginDimensions for Grid {
+        fn size(&self) -> Size {
+            Size::new(4, 2)
+        }
+    }
+
+    impl DrawTarget for Grid {
+        type Error = Infallible;
+
+        type Color = BinaryColor;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                assert!(
+                    self.bounding_box().contains(point),
+                    "{point:?} is outside of the parent"
+                );
+
+                self.pixels[point.y as usize][point.x as usize] = color.is_on();
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn downscales_by_the_parent_ratio() {
+        let mut grid = Grid::default();
+        let mut scaled = Scaled::new(&mut grid, Size::new(8, 4));
+
+        assert_eq!(
+            scaled.map_to_physical(Point::new(6, 2)),
+            Some(Point::new(3, 1))
+        );
+
+        let area = scaled.bounding_box();
+        scaled
+            .draw_iter(area.points().map(|point| Pixel(point, BinaryColor::On)))
+            .unwrap();
+
+        assert_eq!(grid.pixels, [[true; 4]; 2]);
+    }
+
+    #[test]
+    fn upscales_pixels_to_blocks() {
+        let mut grid = Grid::default();
+
+        Scaled::new(&mut grid, Size::new(2, 1))
+            .draw_iter([Pixel(Point::new(0, 0), BinaryColor::On)])
+            .unwrap();
+
+        assert_eq!(
+            grid.pixels,
+            [[true, true, false, false], [true, true, false, false]]
+        );
+
+        let mut grid = Grid::default();
+
+        Scaled::new(&mut grid, Size::new(2, 1))
+            .fill_contiguous(
+                &Rectangle::new(Point::zero(), Size::new(2, 1)),
+                [BinaryColor::Off, BinaryColor::On],
+            )
+            .unwrap();
+
+        assert_eq!(
+            grid.pixels,
+            [[false, false, true, true], [false, false, true, true]]
+        );
+    }
+
+    #[test]
+    fn upscales_rectangles_to_blocks() {
+        let mut grid = Grid::default();
+
+        Scaled::new(&mut grid, Size::new(2, 1))
+            .fill_solid(
+                &Rectangle::new(Point::new(1, 0), Size::new(1, 1)),
+                BinaryColor::On,
+            )
+            .unwrap();
+
+        assert_eq!(
+            grid.pixels,
+            [[false, false, true, true], [false, false, true, true]]
+        );
+    }
+}
@@ -0,0 +1,312 @@
+use core::cell::RefCell;
+
+use embedded_graphics::prelude::{Dimensions, DrawTarget};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{Flushable, Refresh, Viewport};
+
+pub trait BlockingMutex {
+    type Data;
+
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Data) -> R) -> R;
+}
+
+impl<T> BlockingMutex for RefCell<T> {
+    type Data = T;
+
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<T> BlockingMutex for critical_section::Mutex<RefCell<T>> {
+    type Data = T;
+
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        critical_section::with(|cs| f(&mut self.borrow_ref_mut(cs)))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BlockingMutex for std::sync::Mutex<T> {
+    type Data = T;
+
+    fn lock<R>(&self, f: impl FnOnce(&mut Self::Data) -> R) -> R {
+        f(&mut self.lock().unwrap_or_else(|err| err.into_inner()))
+    }
+}
+
+pub struct SharedDisplay<M> {
+    mutex: M,
+}
+
+impl<M> SharedDisplay<M>
+where
+    M: BlockingMutex,
+    M::Data: DrawTarget,
+{
+    pub const fn new(mutex: M) -> Self {
+        Self { mutex }
+    }
+
+    pub fn handle(&self) -> SharedHandle<'_, M> {
+        SharedHandle {
+            display: &self.mutex,
+        }
+    }
+
+    pub fn split<const N: usize>(&mut self, areas: [Rectangle; N]) -> Option<[Viewport<'_, M>; N]> {
+        Viewport::split(&self.mutex, areas)
+    }
+
+    pub fn lock<R>(&self, f: impl FnOnce(&mut M::Data) -> R) -> R {
+        self.mutex.lock(f)
+    }
+
+    pub fn into_inner(self) -> M {
+        self.mutex
+    }
+}
+
+pub struct SharedHandle<'a, M> {
+    display: &'a M,
+}
+
+impl<'a, M> SharedHandle<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: DrawTarget,
+{
+    pub fn lock<R>(&self, f: impl FnOnce(&mut M::Data) -> R) -> R {
+        self.display.lock(f)
+    }
+}
+
+impl<'a, M> Clone for SharedHandle<'a, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, M> Copy for SharedHandle<'a, M> {}
+
+impl<'a, M> DrawTarget for SharedHandle<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: DrawTarget,
+{
+    type Error = <M::Data as DrawTarget>::Error;
+    type Color = <M::Data as DrawTarget>::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.lock(|display| display.draw_iter(pixels))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.display
+            .lock(|display| display.fill_contiguous(area, colors))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.lock(|display| display.fill_solid(area, color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.lock(|display| display.clear(color))
+    }
+}
+
+impl<'a, M> Dimensions for SharedHandle<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.display.lock(|display| display.bounding_box())
+    }
+}
+
+impl<'a, M> Flushable for SharedHandle<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: Flushable,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.display.lock(|display| display.flush())
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.display.lock(|display| display.flush_with(refresh))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::RefCell;
+
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{BlockingMutex, SharedDisplay};
+    use crate::draw_target::{Flushable, Refresh};
+
+    #[derive(Default)]
+    struct Display {
+        pixels: [[bool; 8]; 4],
+        flushes: [Option<Refresh>; 4],
+        flush_count: usize,
+    }
+
+    impl Display {
+        fn lit(&self) -> impl Iterator<Item = Point> + '_ {
+            self.bounding_box()
+                .points()
+                .filter(|point| self.pixels[point.y as usize][point.x as usize])
+        }
+    }
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            Size::new(8, 4)
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Color = BinaryColor;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                if self.bounding_box().contains(point) {
+                    self.pixels[point.y as usize][point.x as usize] = color.is_on();
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Flushable for Display {
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flush_with(Refresh::Partial)
+        }
+
+        fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+            self.flushes[self.flush_count] = Some(refresh);
+            self.flush_count += 1;
+
+            Ok(())
+        }
+    }
+
+    fn draw_through_handles<M>(mutex: M) -> M
+    where
+        M: BlockingMutex<Data = Display>,
+    {
+        let mut display = SharedDisplay::new(mutex);
+
+        let mut first = display.handle();
+        let mut second = first;
+
+        assert_eq!(
+            first.bounding_box(),
+            Rectangle::new(Point::zero(), Size::new(8, 4))
+        );
+
+        first
+            .draw_iter([Pixel(Point::new(1, 1), BinaryColor::On)])
+            .unwrap();
+        second
+            .fill_solid(
+                &Rectangle::new(Point::new(6, 2), Size::new(4, 4)),
+                BinaryColor::On,
+            )
+            .unwrap();
+        second
+            .fill_contiguous(
+                &Rectangle::new(Point::new(0, 3), Size::new(2, 1)),
+                [BinaryColor::Off, BinaryColor::On],
+            )
+            .unwrap();
+
+        first.flush().unwrap();
+        second.flush_with(Refresh::Full).unwrap();
+
+        assert_eq!(first.lock(|display| display.lit().count()), 6);
+
+        // Viewports of a shared display lock the same mutex
+        let [mut viewport] = display
+            .split([Rectangle::new(Point::new(2, 0), Size::new(2, 2))])
+            .unwrap();
+
+        viewport.clear(BinaryColor::On).unwrap();
+
+        display.lock(|display| {
+            assert!(display.lit().eq([
+                Point::new(2, 0),
+                Point::new(3, 0),
+                Point::new(1, 1),
+                Point::new(2, 1),
+                Point::new(3, 1),
+                Point::new(6, 2),
+                Point::new(7, 2),
+                Point::new(1, 3),
+                Point::new(6, 3),
+                Point::new(7, 3),
+            ]));
+            assert_eq!(
+                display.flushes,
+                [Some(Refresh::Partial), Some(Refresh::Full), None, None]
+            );
+        });
+
+        display.into_inner()
+    }
+
+    #[test]
+    fn shares_a_ref_cell() {
+        draw_through_handles(RefCell::new(Display::default()));
+    }
+
+    #[cfg(feature = "critical-section")]
+    #[test]
+    fn shares_a_critical_section_mutex() {
+        draw_through_handles(critical_section::Mutex::new(RefCell::new(
+            Display::default(),
+        )));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shares_a_std_mutex_even_when_poisoned() {
+        use std::sync::Mutex;
+
+        let mutex = Mutex::new(Display::default());
+
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| BlockingMutex::lock(&mutex, |_| panic!("Poisoning the mutex")))
+                .join()
+                .unwrap_err();
+        });
+
+        assert!(mutex.is_poisoned());
+
+        draw_through_handles(mutex);
+    }
+}
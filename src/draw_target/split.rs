@@ -0,0 +1,325 @@
+use core::cell::RefCell;
+
+use embedded_graphics::draw_target::DrawTargetExt;
+use embedded_graphics::prelude::{Dimensions, DrawTarget, OriginDimensions, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{BlockingMutex, Flushable, Refresh, SharedDisplay};
+
+pub struct Viewports<T> {
+    display: SharedDisplay<RefCell<T>>,
+}
+
+impl<T> Viewports<T>
+where
+    T: DrawTarget,
+{
+    pub const fn new(display: T) -> Self {
+        Self {
+            display: SharedDisplay::new(RefCell::new(display)),
+        }
+    }
+
+    pub fn split<const N: usize>(
+        &mut self,
+        areas: [Rectangle; N],
+    ) -> Option<[Viewport<'_, RefCell<T>>; N]> {
+        self.display.split(areas)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.display.into_inner().into_inner()
+    }
+}
+
+pub struct Viewport<'a, M> {
+    display: &'a M,
+    area: Rectangle,
+}
+
+impl<'a, M> Viewport<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: DrawTarget,
+{
+    pub(crate) fn split<const N: usize>(
+        display: &'a M,
+        areas: [Rectangle; N],
+    ) -> Option<[Self; N]> {
+        let bbox = display.lock(|display| display.bounding_box());
+        let areas = areas.map(|area| area.intersection(&bbox));
+
+        let overlapping = areas.iter().enumerate().any(|(index, area)| {
+            areas[index + 1..]
+                .iter()
+                .any(|other| !area.intersection(other).is_zero_sized())
+        });
+
+        if overlapping {
+            warn!("Viewport areas overlap");
+
+            None
+        } else {
+            Some(areas.map(|area| Self { display, area }))
+        }
+    }
+
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+}
+
+impl<'a, M> DrawTarget for Viewport<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: DrawTarget,
+{
+    type Error = <M::Data as DrawTarget>::Error;
+    type Color = <M::Data as DrawTarget>::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let area = self.area;
+
+        self.display.lock(|display| {
+            let mut clipped = display.clipped(&area);
+
+            clipped.cropped(&area).draw_iter(pixels)
+        })
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let viewport = self.area;
+
+        self.display.lock(|display| {
+            let mut clipped = display.clipped(&viewport);
+
+            clipped.cropped(&viewport).fill_contiguous(area, colors)
+        })
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let viewport = self.area;
+
+        self.display.lock(|display| {
+            let mut clipped = display.clipped(&viewport);
+
+            clipped.cropped(&viewport).fill_solid(area, color)
+        })
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.area;
+
+        self.display
+            .lock(|display| display.fill_solid(&area, color))
+    }
+}
+
+impl<'a, M> OriginDimensions for Viewport<'a, M> {
+    fn size(&self) -> Size {
+        self.area.size
+    }
+}
+
+impl<'a, M> Flushable for Viewport<'a, M>
+where
+    M: BlockingMutex,
+    M::Data: Flushable,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_with(Refresh::Partial)
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        let area = self.area;
+
+        self.display
+            .lock(|display| display.flush_area(&area, refresh))
+    }
+
+    fn flush_area(&mut self, area: &Rectangle, refresh: Refresh) -> Result<(), Self::Error> {
+        let area =
+            Rectangle::new(area.top_left + self.area.top_left, area.size).intersection(&self.area);
+
+        self.display
+            .lock(|display| display.flush_area(&area, refresh))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::Viewports;
+    use crate::draw_target::{Flushable, Refresh};
+
+    #[derive(Default)]
+    struct Display {
+        pixels: [[bool; 16]; 8],
+        flushes: [Option<(Rectangle, Refresh)>; 4],
+        flush_count: usize,
+    }
+
+    impl Display {
+        fn lit(&self) -> impl Iterator<Item = Point> + '_ {
+            self.bounding_box()
+                .points()
+                .filter(|point| self.pixels[point.y as usize][point.x as usize])
+        }
+    }
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            Size::new(16, 8)
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Color = BinaryColor;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                assert!(self.bounding_box().contains(point));
+
+                self.pixels[point.y as usize][point.x as usize] = color.is_on();
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Flushable for Display {
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flush_area(&self.bounding_box(), Refresh::Partial)
+        }
+
+        fn flush_area(&mut self, area: &Rectangle, refresh: Refresh) -> Result<(), Self::Error> {
+            self.flushes[self.flush_count] = Some((*area, refresh));
+            self.flush_count += 1;
+
+            Ok(())
+        }
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(width, height))
+    }
+
+    #[test]
+    fn rejects_overlapping_areas() {
+        let mut viewports = Viewports::new(Display::default());
+
+        assert!(viewports
+            .split([rect(0, 0, 8, 8), rect(7, 0, 8, 8)])
+            .is_none());
+
+        // Areas are clipped to the display before checking
+        let [left, right] = viewports
+            .split([rect(-4, 0, 12, 8), rect(8, 4, 16, 16)])
+            .unwrap();
+
+        assert_eq!(left.area(), rect(0, 0, 8, 8));
+        assert_eq!(right.area(), rect(8, 4, 8, 4));
+        assert_eq!(right.size(), Size::new(8, 4));
+    }
+
+    #[test]
+    fn clips_drawing_to_each_viewport() {
+        let mut viewports = Viewports::new(Display::default());
+
+        let [mut left, mut right] = viewports
+            .split([rect(0, 0, 8, 8), rect(8, 2, 8, 4)])
+            .unwrap();
+
+        let on = |x, y| Pixel(Point::new(x, y), BinaryColor::On);
+
+        left.draw_iter([on(1, 1), on(8, 1), on(-1, 0)]).unwrap();
+        right.draw_iter([on(0, 0), on(0, 4), on(7, 3)]).unwrap();
+
+        right
+            .fill_contiguous(
+                &rect(6, 1, 4, 1),
+                [
+                    BinaryColor::On,
+                    BinaryColor::Off,
+                    BinaryColor::On,
+                    BinaryColor::On,
+                ],
+            )
+            .unwrap();
+
+        assert!(viewports.into_inner().lit().eq([
+            Point::new(1, 1),
+            Point::new(8, 2),
+            Point::new(14, 3),
+            Point::new(15, 5),
+        ]));
+    }
+
+    #[test]
+    fn fills_and_clears_only_the_viewport() {
+        let mut viewports = Viewports::new(Display::default());
+
+        let [mut left, mut right] = viewports
+            .split([rect(0, 0, 8, 8), rect(8, 2, 8, 4)])
+            .unwrap();
+
+        right
+            .fill_solid(&rect(-2, -2, 20, 20), BinaryColor::On)
+            .unwrap();
+        left.clear(BinaryColor::On).unwrap();
+        left.fill_solid(&rect(4, 4, 8, 8), BinaryColor::Off)
+            .unwrap();
+
+        let mut lit = rect(0, 0, 8, 8)
+            .points()
+            .chain(rect(8, 2, 8, 4).points())
+            .filter(|point| !rect(4, 4, 4, 4).contains(*point));
+
+        let display = viewports.into_inner();
+
+        assert_eq!(display.lit().count(), lit.clone().count());
+        assert!(lit.all(|point| display.pixels[point.y as usize][point.x as usize]));
+    }
+
+    #[test]
+    fn flushes_the_area_of_the_viewport() {
+        let mut viewports = Viewports::new(Display::default());
+
+        let [mut left, mut right] = viewports
+            .split([rect(0, 0, 8, 8), rect(8, 2, 8, 4)])
+            .unwrap();
+
+        right.flush().unwrap();
+        left.flush_with(Refresh::Full).unwrap();
+        right
+            .flush_area(&rect(2, 1, 10, 10), Refresh::Partial)
+            .unwrap();
+
+        assert_eq!(
+            viewports.into_inner().flushes,
+            [
+                Some((rect(8, 2, 8, 4), Refresh::Partial)),
+                Some((rect(0, 0, 8, 8), Refresh::Full)),
+                Some((rect(10, 3, 6, 3), Refresh::Partial)),
+                None,
+            ]
+        );
+    }
+}
@@ -0,0 +1,680 @@
+use embedded_graphics::image::ImageDrawable;
+use embedded_graphics::prelude::{
+    Dimensions, DrawTarget, IntoStorage, OriginDimensions, PixelColor, Point, PointsIter, Size,
+};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::PackedFramebuffer;
+
+#[derive(Copy, Clone)]
+pub struct Sprite<'a, C> {
+    data: &'a [u8],
+    mask: Option<&'a [u8]>,
+    transparent: Option<C>,
+    size: Size,
+}
+
+impl<'a, C> Sprite<'a, C>
+where
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    pub const fn new(data: &'a [u8], size: Size) -> Self {
+        assert!(
+            data.len() >= Self::buffer_size(size),
+            "The sprite data is smaller than the sprite"
+        );
+
+        Self {
+            data,
+            mask: None,
+            transparent: None,
+            size,
+        }
+    }
+
+    pub const fn with_mask(self, mask: &'a [u8]) -> Self {
+        assert!(
+            mask.len() >= Self::mask_size(self.size),
+            "The sprite mask is smaller than the sprite"
+        );
+
+        Self {
+            data: self.data,
+            mask: Some(mask),
+            transparent: self.transparent,
+            size: self.size,
+        }
+    }
+
+    pub const fn with_transparent(self, color: C) -> Self {
+        Self {
+            data: self.data,
+            mask: self.mask,
+            transparent: Some(color),
+            size: self.size,
+        }
+    }
+
+    pub const fn buffer_size(size: Size) -> usize {
+        row_size(size.width as _, PackedFramebuffer::<C>::BITS_PER_PIXEL) * size.height as usize
+    }
+
+    pub const fn mask_size(size: Size) -> usize {
+        row_size(size.width as _, 1) * size.height as usize
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn mask(&self) -> Option<&'a [u8]> {
+        self.mask
+    }
+
+    pub fn transparent(&self) -> Option<C> {
+        self.transparent
+    }
+
+    pub fn pixel(&self, point: Point) -> Option<C> {
+        if Rectangle::new(Point::zero(), self.size).contains(point) {
+            let color = self.color(point);
+
+            self.visible(point, color).then_some(color)
+        } else {
+            None
+        }
+    }
+
+    fn is_opaque(&self) -> bool {
+        self.mask.is_none() && self.transparent.is_none()
+    }
+
+    fn color(&self, point: Point) -> C {
+        let bits_per_pixel = PackedFramebuffer::<C>::BITS_PER_PIXEL;
+        let bit = point.x as usize * bits_per_pixel;
+
+        let byte = self.row(point.y as _)[bit / 8];
+
+        ((byte >> (bit % 8)) & ((1_u16 << bits_per_pixel) - 1) as u8).into()
+    }
+
+    fn visible(&self, point: Point, color: C) -> bool {
+        let masked = self.mask_row(point.y as _).map_or(false, |mask| {
+            mask[point.x as usize / 8] & (1 << (point.x as usize % 8)) == 0
+        });
+
+        !masked && self.transparent != Some(color)
+    }
+
+    fn row(&self, y: usize) -> &'a [u8] {
+        let size = row_size(self.size.width as _, PackedFramebuffer::<C>::BITS_PER_PIXEL);
+
+        &self.data[y * size..][..size]
+    }
+
+    fn mask_row(&self, y: usize) -> Option<&'a [u8]> {
+        let size = row_size(self.size.width as _, 1);
+
+        self.mask.map(|mask| &mask[y * size..][..size])
+    }
+
+    fn draw_area<D>(&self, target: &mut D, area: &Rectangle, offset: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        if self.is_opaque() {
+            target.fill_contiguous(
+                &Rectangle::new(area.top_left - offset, area.size),
+                area.points().map(|point| self.color(point)),
+            )
+        } else {
+            target.draw_iter(
+                area.points().filter_map(|point| {
+                    self.pixel(point).map(|color| Pixel(point - offset, color))
+                }),
+            )
+        }
+    }
+}
+
+impl<'a, C> OriginDimensions for Sprite<'a, C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<'a, C> ImageDrawable for Sprite<'a, C>
+where
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Color = C;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.draw_area(target, &self.bounding_box(), Point::zero())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = area.intersection(&self.bounding_box());
+
+        self.draw_area(target, &area, area.top_left)
+    }
+}
+
+impl<'a, C> PackedFramebuffer<'a, C>
+where
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    pub fn blit(&mut self, sprite: &Sprite<'_, C>, at: Point) {
+        let bits_per_pixel = Self::BITS_PER_PIXEL;
+        let pixels_per_byte = Self::PIXELS_PER_BYTE as i32;
+        let pixel_mask = ((1_u16 << bits_per_pixel) - 1) as u8;
+
+        let area = Rectangle::new(at, sprite.size).intersection(&self.bounding_box());
+        if area.is_zero_sized() {
+            warn!("Blitting a sprite outside of the framebuffer at {:?}", at);
+
+            return;
+        }
+
+        let x_start = area.top_left.x;
+        let x_end = x_start + area.size.width as i32;
+
+        let bytes_per_row = self.bytes_per_row();
+        let transparent = sprite.transparent.map(IntoStorage::into_storage);
+
+        for y in area.rows() {
+            let src = sprite.row((y - at.y) as _);
+            let src_mask = sprite.mask_row((y - at.y) as _);
+
+            let row = &mut self.bytes_mut()[y as usize * bytes_per_row..][..bytes_per_row];
+
+            let first = (x_start / pixels_per_byte) as usize;
+            let last = ((x_end - 1) / pixels_per_byte) as usize;
+
+            for (index, byte) in row.iter_mut().enumerate().take(last + 1).skip(first) {
+                let x = index as i32 * pixels_per_byte;
+
+                let lo = (x_start - x).clamp(0, pixels_per_byte) as usize * bits_per_pixel;
+                let hi = (x_end - x).clamp(0, pixels_per_byte) as usize * bits_per_pixel;
+
+                let mut write_mask = ((1_u16 << hi) - (1_u16 << lo)) as u8;
+
+                let value = extract(src, (x - at.x) as isize * bits_per_pixel as isize);
+
+                if let Some(src_mask) = src_mask {
+                    let bits = extract(src_mask, (x - at.x) as isize);
+
+                    for pixel in 0..pixels_per_byte as usize {
+                        if bits & (1 << pixel) == 0 {
+                            write_mask &= !(pixel_mask << (pixel * bits_per_pixel));
+                        }
+                    }
+                }
+
+                if let Some(transparent) = transparent {
+                    for pixel in 0..pixels_per_byte as usize {
+                        let shift = pixel * bits_per_pixel;
+
+                        if (value >> shift) & pixel_mask == transparent {
+                            write_mask &= !(pixel_mask << shift);
+                        }
+                    }
+                }
+
+                *byte = (*byte & !write_mask) | (value & write_mask);
+            }
+        }
+    }
+}
+
+const fn row_size(width: usize, bits_per_pixel: usize) -> usize {
+    (width * bits_per_pixel + 7) / 8
+}
+
+fn extract(row: &[u8], bit: isize) -> u8 {
+    let index = bit.div_euclid(8);
+    let shift = bit.rem_euclid(8) as u32;
+
+    let byte = |index: isize| {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| row.get(index))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let lo = byte(index) >> shift;
+
+    if shift > 0 {
+        lo | byte(index + 1) << (8 - shift)
+    } else {
+        lo
+    }
+}
+
+#[cfg(feature = "std")]
+pub use netpbm::*;
+
+#[cfg(feature = "std")]
+mod netpbm {
+    use std::format;
+    use std::io::{self, Write};
+    use std::vec;
+    use std::vec::Vec;
+
+    use embedded_graphics::prelude::{IntoStorage, PixelColor, Size};
+
+    use crate::draw_target::PackedFramebuffer;
+
+    use super::{row_size, Sprite};
+
+    pub struct SpriteData {
+        size: Size,
+        bits_per_pixel: usize,
+        data: Vec<u8>,
+    }
+
+    impl SpriteData {
+        pub fn from_netpbm<C>(input: &[u8]) -> io::Result<Self>
+        where
+            C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+        {
+            Self::parse(input, PackedFramebuffer::<C>::BITS_PER_PIXEL)
+        }
+
+        pub fn mask_from_netpbm(input: &[u8]) -> io::Result<Self> {
+            Self::parse(input, 1)
+        }
+
+        pub fn size(&self) -> Size {
+            self.size
+        }
+
+        pub fn data(&self) -> &[u8] {
+            &self.data
+        }
+
+        pub fn sprite<C>(&self) -> Sprite<'_, C>
+        where
+            C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+        {
+            assert_eq!(
+                self.bits_per_pixel,
+                PackedFramebuffer::<C>::BITS_PER_PIXEL,
+                "The sprite data has a different color depth"
+            );
+
+            Sprite::new(&self.data, self.size)
+        }
+
+        pub fn write_rust(&self, name: &str, out: &mut impl Write) -> io::Result<()> {
+            writeln!(out, "pub const {}_WIDTH: u32 = {};", name, self.size.width)?;
+            writeln!(
+                out,
+                "pub const {}_HEIGHT: u32 = {};",
+                name, self.size.height
+            )?;
+            write!(out, "pub static {}: [u8; {}] = [", name, self.data.len())?;
+
+            for (index, byte) in self.data.iter().enumerate() {
+                if index % 16 == 0 {
+                    write!(out, "\n   ")?;
+                }
+
+                write!(out, " 0x{:02x},", byte)?;
+            }
+
+            writeln!(out, "\n];")
+        }
+
+        fn parse(input: &[u8], bits_per_pixel: usize) -> io::Result<Self> {
+            let mut header = Header { input, offset: 0 };
+
+            let magic = header.token()?;
+            let bitmap = matches!(magic, b"P1" | b"P4");
+            let binary = matches!(magic, b"P4" | b"P5");
+
+            if !matches!(magic, b"P1" | b"P2" | b"P4" | b"P5") {
+                return Err(invalid("Not a PBM or PGM image"));
+            }
+
+            let width = header.number()?;
+            let height = header.number()?;
+            let max = if bitmap { 1 } else { header.number()? };
+
+            if max == 0 || max > u16::MAX as usize {
+                return Err(invalid("Invalid maximum gray value"));
+            }
+
+            let levels = (1 << bits_per_pixel) - 1;
+
+            let mut data = vec![0; row_size(width, bits_per_pixel) * height];
+            let mut raster = Raster {
+                header,
+                binary,
+                bitmap,
+                wide: max > u8::MAX as usize,
+                width,
+            };
+
+            if binary {
+                raster.header.offset += 1;
+            }
+
+            for y in 0..height {
+                for x in 0..width {
+                    let mut value = raster.sample(x, y)?;
+
+                    if value > max {
+                        return Err(invalid("Sample exceeds maximum gray value"));
+                    }
+
+                    if bitmap {
+                        value = 1 - value;
+                    }
+
+                    let level = ((value * levels + max / 2) / max) as u8;
+
+                    let bit = x * bits_per_pixel;
+                    data[y * row_size(width, bits_per_pixel) + bit / 8] |= level << (bit % 8);
+                }
+            }
+
+            Ok(Self {
+                size: Size::new(width as _, height as _),
+                bits_per_pixel,
+                data,
+            })
+        }
+    }
+
+    struct Header<'a> {
+        input: &'a [u8],
+        offset: usize,
+    }
+
+    impl<'a> Header<'a> {
+        fn token(&mut self) -> io::Result<&'a [u8]> {
+            loop {
+                match self.input.get(self.offset) {
+                    Some(b'#') => {
+                        while !matches!(self.input.get(self.offset), Some(b'\n') | None) {
+                            self.offset += 1;
+                        }
+                    }
+                    Some(byte) if byte.is_ascii_whitespace() => self.offset += 1,
+                    Some(_) => break,
+                    None => return Err(invalid("Truncated image")),
+                }
+            }
+
+            let start = self.offset;
+            while matches!(self.input.get(self.offset), Some(byte) if !byte.is_ascii_whitespace()) {
+                self.offset += 1;
+            }
+
+            Ok(&self.input[start..self.offset])
+        }
+
+        fn number(&mut self) -> io::Result<usize> {
+            let token = self.token()?;
+
+            core::str::from_utf8(token)
+                .ok()
+                .and_then(|token| token.parse().ok())
+                .ok_or_else(|| invalid(&format!("Invalid number {:?}", token)))
+        }
+    }
+
+    struct Raster<'a> {
+        header: Header<'a>,
+        binary: bool,
+        bitmap: bool,
+        wide: bool,
+        width: usize,
+    }
+
+    impl<'a> Raster<'a> {
+        fn sample(&mut self, x: usize, y: usize) -> io::Result<usize> {
+            if !self.binary {
+                if self.bitmap {
+                    let token = self.header.token()?;
+
+                    if token.len() > 1 {
+                        self.header.offset -= token.len() - 1;
+                    }
+
+                    return match token[0] {
+                        b'0' => Ok(0),
+                        b'1' => Ok(1),
+                        _ => Err(invalid("Invalid bitmap sample")),
+                    };
+                }
+
+                return self.header.number();
+            }
+
+            let input = &self.header.input[self.header.offset..];
+
+            if self.bitmap {
+                let byte = input
+                    .get(y * row_size(self.width, 1) + x / 8)
+                    .ok_or_else(|| invalid("Truncated image"))?;
+
+                Ok(((byte >> (7 - x % 8)) & 1) as _)
+            } else if self.wide {
+                let offset = (y * self.width + x) * 2;
+
+                input
+                    .get(offset..offset + 2)
+                    .map(|sample| u16::from_be_bytes([sample[0], sample[1]]) as _)
+                    .ok_or_else(|| invalid("Truncated image"))
+            } else {
+                input
+                    .get(y * self.width + x)
+                    .map(|sample| *sample as _)
+                    .ok_or_else(|| invalid("Truncated image"))
+            }
+        }
+    }
+
+    fn invalid(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::io;
+        use std::string::ToString;
+
+        use embedded_graphics::pixelcolor::raw::RawU2;
+        use embedded_graphics::prelude::{PixelColor, Size};
+
+        use super::SpriteData;
+
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct Gray(u8);
+
+        impl PixelColor for Gray {
+            type Raw = RawU2;
+        }
+
+        impl From<Gray> for RawU2 {
+            fn from(color: Gray) -> Self {
+                RawU2::new(color.0)
+            }
+        }
+
+        impl From<u8> for Gray {
+            fn from(bits: u8) -> Self {
+                Gray(bits)
+            }
+        }
+
+        fn error(input: &[u8]) -> io::Error {
+            match SpriteData::from_netpbm::<Gray>(input) {
+                Ok(_) => panic!("{:?} parsed", input),
+                Err(err) => err,
+            }
+        }
+
+        #[test]
+        fn parses_plain_bitmaps() {
+            let data =
+                SpriteData::from_netpbm::<Gray>(b"P1\n# comment\n3 2\n1 0 1\n011\n").unwrap();
+
+            assert_eq!(data.size(), Size::new(3, 2));
+            // Black PBM pixels are dark, so they map to 0 and white ones to the brightest color
+            assert_eq!(data.data(), &[0b00_11_00, 0b00_00_11]);
+
+            let mask = SpriteData::mask_from_netpbm(b"P1 3 2 1 0 1 0 1 1").unwrap();
+
+            assert_eq!(mask.data(), &[0b010, 0b001]);
+        }
+
+        #[test]
+        fn parses_binary_bitmaps() {
+            let data = SpriteData::mask_from_netpbm(b"P4\n10 2\n\xa0\x40\x00\xc0").unwrap();
+
+            assert_eq!(data.size(), Size::new(10, 2));
+            assert_eq!(data.data(), &[0xfa, 0x01, 0xff, 0x00]);
+        }
+
+        #[test]
+        fn parses_graymaps() {
+            let plain = SpriteData::from_netpbm::<Gray>(b"P2 4 1 255 0 85 170 255").unwrap();
+
+            assert_eq!(plain.data(), &[0b11_10_01_00]);
+
+            let binary = SpriteData::from_netpbm::<Gray>(b"P5 4 1 3 \x00\x01\x02\x03").unwrap();
+
+            assert_eq!(binary.data(), plain.data());
+
+            let wide = SpriteData::from_netpbm::<Gray>(b"P5 2 1 65535 \xff\xff\x55\x55").unwrap();
+
+            assert_eq!(wide.data(), &[0b01_11]);
+        }
+
+        #[test]
+        fn rejects_truncated_images() {
+            for input in [
+                &b"P2 2 2 3 0 1 2"[..],
+                b"P5 2 2 3 \x00\x01\x02",
+                b"P5 2 1 1000 \x00\x01\x02",
+                b"P4 9 2 \xff\xff\xff",
+                b"P1 2 2 0 1 1",
+                b"P2 2",
+            ] {
+                assert_eq!(error(input).to_string(), "Truncated image");
+            }
+        }
+
+        #[test]
+        fn rejects_bad_samples() {
+            assert_eq!(
+                error(b"P2 2 1 3 1 4").to_string(),
+                "Sample exceeds maximum gray value"
+            );
+            assert_eq!(
+                error(b"P5 2 1 100 \x00\xc8").to_string(),
+                "Sample exceeds maximum gray value"
+            );
+            assert_eq!(error(b"P1 2 1 0 2").to_string(), "Invalid bitmap sample");
+            assert_eq!(error(b"P2 2 1 3 1 x").to_string(), "Invalid number [120]");
+            assert_eq!(
+                error(b"P2 2 1 0 0 0").to_string(),
+                "Invalid maximum gray value"
+            );
+            assert_eq!(error(b"P3 2 1 3 0 0").to_string(), "Not a PBM or PGM image");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::image::{GetPixel, Image};
+    use embedded_graphics::pixelcolor::raw::RawU2;
+    use embedded_graphics::prelude::{Dimensions, Drawable, PixelColor, Point, PointsIter, Size};
+
+    use super::Sprite;
+    use crate::draw_target::PackedFramebuffer;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl PixelColor for Gray {
+        type Raw = RawU2;
+    }
+
+    impl From<Gray> for RawU2 {
+        fn from(color: Gray) -> Self {
+            RawU2::new(color.0)
+        }
+    }
+
+    impl From<u8> for Gray {
+        fn from(bits: u8) -> Self {
+            Gray(bits)
+        }
+    }
+
+    // 5x3 pixels, 2 bytes per row, with every color in every row
+    const DATA: [u8; 6] = [
+        0b11_10_01_00,
+        0b01,
+        0b10_01_00_11,
+        0b11,
+        0b00_11_10_01,
+        0b10,
+    ];
+    const MASK: [u8; 3] = [0b10111, 0b01101, 0b11010];
+
+    const BACKGROUND: u8 = 0b01_10_01_10;
+
+    fn assert_blit(sprite: &Sprite<'_, Gray>) {
+        for y in -4..6 {
+            for x in -6..14 {
+                let at = Point::new(x, y);
+
+                let mut buf = [BACKGROUND; 12];
+                let mut fb = PackedFramebuffer::<Gray>::new(&mut buf, 12, 4);
+                fb.blit(sprite, at);
+
+                let mut expected_buf = [BACKGROUND; 12];
+                let mut expected = PackedFramebuffer::<Gray>::new(&mut expected_buf, 12, 4);
+                Image::new(sprite, at).draw(&mut expected).unwrap();
+
+                for point in fb.bounding_box().points() {
+                    assert_eq!(fb.pixel(point), expected.pixel(point), "{at:?} {point:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blits_opaque_sprites() {
+        assert_blit(&Sprite::new(&DATA, Size::new(5, 3)));
+    }
+
+    #[test]
+    fn blits_masked_sprites() {
+        assert_blit(&Sprite::new(&DATA, Size::new(5, 3)).with_mask(&MASK));
+    }
+
+    #[test]
+    fn blits_transparent_sprites() {
+        for color in 0..4 {
+            let sprite = Sprite::new(&DATA, Size::new(5, 3)).with_transparent(Gray(color));
+
+            assert_blit(&sprite);
+            assert_blit(&sprite.with_mask(&MASK));
+        }
+    }
+}
@@ -0,0 +1,510 @@
+use core::marker::PhantomData;
+
+use embedded_graphics::prelude::{
+    DrawTarget, IntoStorage, OriginDimensions, PixelColor, Point, Size,
+};
+use embedded_graphics::Pixel;
+
+use embedded_io::Write;
+
+use super::{Flushable, PackedFramebuffer};
+
+const MAGIC: &[u8; 4] = b"GFXS";
+const VERSION: u8 = 1;
+
+const MSG_HEADER: u8 = 0;
+const MSG_RECT: u8 = 1;
+const MSG_END_OF_FRAME: u8 = 2;
+
+const ENCODING_RAW: u8 = 0;
+const ENCODING_RLE: u8 = 1;
+
+const MAX_RUN: usize = 64;
+
+pub struct StreamEncoder<W, C> {
+    writer: W,
+    size: Size,
+    rle: bool,
+    header_sent: bool,
+    run: [u8; MAX_RUN],
+    run_start: Point,
+    run_len: usize,
+    _color: PhantomData<C>,
+}
+
+impl<W, C> StreamEncoder<W, C>
+where
+    W: Write,
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    pub const fn new(writer: W, size: Size, rle: bool) -> Self {
+        Self {
+            writer,
+            size,
+            rle,
+            header_sent: false,
+            run: [0; MAX_RUN],
+            run_start: Point::zero(),
+            run_len: 0,
+            _color: PhantomData,
+        }
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn push(&mut self, point: Point, color: C) -> Result<(), W::Error> {
+        if self.run_len > 0
+            && (self.run_len == MAX_RUN
+                || point.y != self.run_start.y
+                || point.x != self.run_start.x + self.run_len as i32)
+        {
+            self.write_run()?;
+        }
+
+        if self.run_len == 0 {
+            self.run_start = point;
+        }
+
+        self.run[self.run_len] = color.into_storage();
+        self.run_len += 1;
+
+        Ok(())
+    }
+
+    fn write_run(&mut self) -> Result<(), W::Error> {
+        if self.run_len == 0 {
+            return Ok(());
+        }
+
+        self.write_header()?;
+
+        let run = &self.run[..self.run_len];
+
+        let mut packed = [0_u8; MAX_RUN];
+        let packed_len = pack(run, PackedFramebuffer::<C>::BITS_PER_PIXEL, &mut packed);
+
+        let mut rle = [0_u8; MAX_RUN * 2];
+        let rle_len = if self.rle {
+            encode_rle(run, &mut rle)
+        } else {
+            usize::MAX
+        };
+
+        let (encoding, data) = if rle_len < packed_len {
+            (ENCODING_RLE, &rle[..rle_len])
+        } else {
+            (ENCODING_RAW, &packed[..packed_len])
+        };
+
+        let mut msg = [0_u8; 10];
+        msg[0] = MSG_RECT;
+        msg[1..3].copy_from_slice(&(self.run_start.x as u16).to_le_bytes());
+        msg[3..5].copy_from_slice(&(self.run_start.y as u16).to_le_bytes());
+        msg[5..7].copy_from_slice(&(self.run_len as u16).to_le_bytes());
+        msg[7..9].copy_from_slice(&1_u16.to_le_bytes());
+        msg[9] = encoding;
+
+        self.writer.write_all(&msg)?;
+        self.writer.write_all(data)?;
+
+        self.run_len = 0;
+
+        Ok(())
+    }
+
+    fn write_header(&mut self) -> Result<(), W::Error> {
+        if !self.header_sent {
+            let mut msg = [0_u8; 11];
+            msg[0] = MSG_HEADER;
+            msg[1..5].copy_from_slice(MAGIC);
+            msg[5] = VERSION;
+            msg[6..8].copy_from_slice(&(self.size.width as u16).to_le_bytes());
+            msg[8..10].copy_from_slice(&(self.size.height as u16).to_le_bytes());
+            msg[10] = PackedFramebuffer::<C>::BITS_PER_PIXEL as u8;
+
+            self.writer.write_all(&msg)?;
+
+            self.header_sent = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W, C> OriginDimensions for StreamEncoder<W, C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<W, C> DrawTarget for StreamEncoder<W, C>
+where
+    W: Write,
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    type Error = W::Error;
+
+    type Color = C;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            if pixel.0.x >= 0
+                && pixel.0.y >= 0
+                && pixel.0.x < self.size.width as i32
+                && pixel.0.y < self.size.height as i32
+            {
+                self.push(pixel.0, pixel.1)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W, C> Flushable for StreamEncoder<W, C>
+where
+    W: Write,
+    C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.write_run()?;
+        self.write_header()?;
+
+        self.writer.write_all(&[MSG_END_OF_FRAME])?;
+        self.writer.flush()
+    }
+}
+
+fn pack(colors: &[u8], bits_per_pixel: usize, packed: &mut [u8]) -> usize {
+    let pixels_per_byte = 8 / bits_per_pixel;
+    let len = (colors.len() + pixels_per_byte - 1) / pixels_per_byte;
+
+    packed[..len].fill(0);
+
+    for (index, color) in colors.iter().enumerate() {
+        packed[index / pixels_per_byte] |= color << ((index % pixels_per_byte) * bits_per_pixel);
+    }
+
+    len
+}
+
+fn encode_rle(colors: &[u8], rle: &mut [u8]) -> usize {
+    let mut len = 0;
+    let mut colors = colors.iter().peekable();
+
+    while let Some(color) = colors.next() {
+        let mut count = 1_u8;
+        while count < u8::MAX && colors.next_if_eq(&color).is_some() {
+            count += 1;
+        }
+
+        rle[len] = count;
+        rle[len + 1] = *color;
+        len += 2;
+    }
+
+    len
+}
+
+#[cfg(feature = "std")]
+pub use decoder::*;
+
+#[cfg(feature = "std")]
+mod decoder {
+    use std::io::{self, Read};
+    use std::vec;
+    use std::vec::Vec;
+
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, IntoStorage, PixelColor, Point, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+
+    use crate::draw_target::PackedFramebuffer;
+
+    use super::{
+        ENCODING_RAW, ENCODING_RLE, MAGIC, MSG_END_OF_FRAME, MSG_HEADER, MSG_RECT, VERSION,
+    };
+
+    pub struct StreamDecoder<R> {
+        reader: R,
+        size: Size,
+        bits_per_pixel: usize,
+    }
+
+    impl<R> StreamDecoder<R>
+    where
+        R: Read,
+    {
+        pub fn new(mut reader: R) -> io::Result<Self> {
+            let mut msg = [0_u8; 11];
+            reader.read_exact(&mut msg)?;
+
+            if msg[0] != MSG_HEADER || &msg[1..5] != MAGIC || msg[5] != VERSION {
+                return Err(invalid("Not a framebuffer stream"));
+            }
+
+            let bits_per_pixel = msg[10] as usize;
+            if !matches!(bits_per_pixel, 1 | 2 | 4 | 8) {
+                return Err(invalid("Unsupported bits per pixel"));
+            }
+
+            Ok(Self {
+                reader,
+                size: Size::new(
+                    u16::from_le_bytes([msg[6], msg[7]]) as _,
+                    u16::from_le_bytes([msg[8], msg[9]]) as _,
+                ),
+                bits_per_pixel,
+            })
+        }
+
+        pub fn size(&self) -> Size {
+            self.size
+        }
+
+        pub fn buffer_size(&self) -> usize {
+            self.size.width as usize * self.size.height as usize / (8 / self.bits_per_pixel)
+        }
+
+        pub fn into_reader(self) -> R {
+            self.reader
+        }
+
+        pub fn decode_frame<C>(&mut self, fb: &mut PackedFramebuffer<'_, C>) -> io::Result<bool>
+        where
+            C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+        {
+            if PackedFramebuffer::<C>::BITS_PER_PIXEL != self.bits_per_pixel {
+                return Err(invalid("Color depth does not match the stream"));
+            }
+
+            let mut first = true;
+
+            loop {
+                let mut msg = [0_u8; 1];
+                match self.reader.read_exact(&mut msg) {
+                    Err(err) if first && err.kind() == io::ErrorKind::UnexpectedEof => {
+                        return Ok(false)
+                    }
+                    other => other?,
+                }
+
+                first = false;
+
+                match msg[0] {
+                    MSG_RECT => self.decode_rect(fb)?,
+                    MSG_END_OF_FRAME => return Ok(true),
+                    _ => return Err(invalid("Unknown message")),
+                }
+            }
+        }
+
+        fn decode_rect<C>(&mut self, fb: &mut PackedFramebuffer<'_, C>) -> io::Result<()>
+        where
+            C: PixelColor + IntoStorage<Storage = u8> + From<u8>,
+        {
+            let mut msg = [0_u8; 9];
+            self.reader.read_exact(&mut msg)?;
+
+            let area = Rectangle::new(
+                Point::new(
+                    u16::from_le_bytes([msg[0], msg[1]]) as _,
+                    u16::from_le_bytes([msg[2], msg[3]]) as _,
+                ),
+                Size::new(
+                    u16::from_le_bytes([msg[4], msg[5]]) as _,
+                    u16::from_le_bytes([msg[6], msg[7]]) as _,
+                ),
+            );
+
+            if area.intersection(&fb.bounding_box()) != area {
+                return Err(invalid("Rectangle outside of the framebuffer"));
+            }
+
+            let len = (area.size.width * area.size.height) as usize;
+
+            let colors = match msg[8] {
+                ENCODING_RAW => self.read_raw(len)?,
+                ENCODING_RLE => self.read_rle(len)?,
+                _ => return Err(invalid("Unknown encoding")),
+            };
+
+            fb.fill_contiguous(&area, colors.into_iter().map(C::from))
+                .unwrap();
+
+            Ok(())
+        }
+
+        fn read_raw(&mut self, len: usize) -> io::Result<Vec<u8>> {
+            let pixels_per_byte = 8 / self.bits_per_pixel;
+            let mask = ((1_u16 << self.bits_per_pixel) - 1) as u8;
+
+            let mut packed = vec![0; (len + pixels_per_byte - 1) / pixels_per_byte];
+            self.reader.read_exact(&mut packed)?;
+
+            Ok((0..len)
+                .map(|index| {
+                    (packed[index / pixels_per_byte]
+                        >> ((index % pixels_per_byte) * self.bits_per_pixel))
+                        & mask
+                })
+                .collect())
+        }
+
+        fn read_rle(&mut self, len: usize) -> io::Result<Vec<u8>> {
+            let mut colors = Vec::with_capacity(len);
+
+            while colors.len() < len {
+                let mut pair = [0_u8; 2];
+                self.reader.read_exact(&mut pair)?;
+
+                if pair[0] == 0 || colors.len() + pair[0] as usize > len {
+                    return Err(invalid("Corrupted RLE data"));
+                }
+
+                colors.extend(core::iter::repeat(pair[1]).take(pair[0] as _));
+            }
+
+            Ok(colors)
+        }
+    }
+
+    fn invalid(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::convert::Infallible;
+
+    use std::vec::Vec;
+
+    use embedded_graphics::pixelcolor::raw::RawU2;
+    use embedded_graphics::prelude::{DrawTarget, PixelColor, Point, Size};
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use embedded_io::{ErrorType, Write};
+
+    use crate::draw_target::{
+        Flushable, Framebuffer, OwnedDrawTargetExt, PackedFramebuffer, StaticFramebuffer,
+    };
+
+    use super::{StreamDecoder, StreamEncoder};
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    struct Gray(u8);
+
+    impl PixelColor for Gray {
+        type Raw = RawU2;
+    }
+
+    impl From<Gray> for RawU2 {
+        fn from(color: Gray) -> Self {
+            RawU2::new(color.0)
+        }
+    }
+
+    impl From<u8> for Gray {
+        fn from(value: u8) -> Self {
+            Gray(value)
+        }
+    }
+
+    #[derive(Default)]
+    struct Sink(Vec<u8>);
+
+    impl ErrorType for Sink {
+        type Error = Infallible;
+    }
+
+    impl Write for Sink {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            self.0.extend_from_slice(buf);
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn draw<D>(target: &mut D, frame: u8)
+    where
+        D: DrawTarget<Color = Gray>,
+        D::Error: core::fmt::Debug,
+    {
+        if frame == 0 {
+            target
+                .fill_solid(&Rectangle::new(Point::zero(), Size::new(16, 8)), Gray(1))
+                .unwrap();
+            target
+                .fill_solid(&Rectangle::new(Point::new(2, 3), Size::new(9, 2)), Gray(3))
+                .unwrap();
+        } else {
+            target
+                .fill_solid(&Rectangle::new(Point::new(5, 1), Size::new(3, 6)), Gray(2))
+                .unwrap();
+            target
+                .draw_iter([
+                    Pixel(Point::new(15, 7), Gray(0)),
+                    Pixel(Point::new(0, 0), Gray(3)),
+                ])
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut sink = Sink::default();
+        let mut expected = StaticFramebuffer::<Gray, 16, 8, 32>::new();
+
+        {
+            let mut draw_buf = [0; 32];
+            let mut reference_buf = [0; 32];
+
+            let mut display = StreamEncoder::<_, Gray>::new(&mut sink, Size::new(16, 8), true)
+                .owned_buffered(&mut draw_buf, &mut reference_buf);
+
+            for frame in 0..2 {
+                draw(&mut display, frame);
+                draw(&mut expected, frame);
+
+                display.flush().unwrap();
+            }
+        }
+
+        let mut decoder = StreamDecoder::new(&sink.0[..]).unwrap();
+        assert_eq!(decoder.size(), Size::new(16, 8));
+
+        let mut buf = [0; 32];
+        let mut decoded = PackedFramebuffer::<Gray>::new(&mut buf, 16, 8);
+
+        let mut first = StaticFramebuffer::<Gray, 16, 8, 32>::new();
+        draw(&mut first, 0);
+
+        assert!(decoder.decode_frame(&mut decoded).unwrap());
+        assert_eq!(decoded.bytes(), first.packed().bytes());
+
+        assert!(decoder.decode_frame(&mut decoded).unwrap());
+        assert_eq!(decoded.bytes(), expected.packed().bytes());
+
+        assert!(!decoder.decode_frame(&mut decoded).unwrap());
+    }
+}
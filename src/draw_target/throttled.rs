@@ -0,0 +1,309 @@
+use core::time::Duration;
+
+use embedded_graphics::prelude::{Dimensions, DrawTarget, Point};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{Clock, DisplayMapping, Flushable, Refresh};
+
+pub struct Throttled<T, C> {
+    target: T,
+    clock: C,
+    interval: Duration,
+    last: Option<Duration>,
+    pending: Option<Refresh>,
+}
+
+impl<T, C> Throttled<T, C>
+where
+    T: Flushable,
+    C: Clock,
+{
+    pub(crate) fn new(target: T, clock: C, max_flushes_per_sec: u32) -> Self {
+        Self {
+            target,
+            clock,
+            interval: Self::interval_for(max_flushes_per_sec),
+            last: None,
+            pending: None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_max_flushes_per_sec(&mut self, max_flushes_per_sec: u32) {
+        self.interval = Self::interval_for(max_flushes_per_sec);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn next_flush_at(&self) -> Option<Duration> {
+        self.pending.map(|_| self.due_at())
+    }
+
+    pub fn poll_flush(&mut self, now: Duration) -> Result<bool, T::Error> {
+        if let Some(refresh) = self.pending {
+            if now >= self.due_at() {
+                self.flush_now(now, refresh)?;
+
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.target
+    }
+
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.target
+    }
+
+    pub fn into_inner(self) -> T {
+        self.target
+    }
+
+    fn interval_for(max_flushes_per_sec: u32) -> Duration {
+        if max_flushes_per_sec == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs(1) / max_flushes_per_sec
+        }
+    }
+
+    fn due_at(&self) -> Duration {
+        self.last
+            .map_or(Duration::ZERO, |last| last + self.interval)
+    }
+
+    fn flush_now(&mut self, now: Duration, refresh: Refresh) -> Result<(), T::Error> {
+        self.pending = None;
+        self.last = Some(now);
+
+        self.target.flush_with(refresh)
+    }
+}
+
+impl<T, C> DrawTarget for Throttled<T, C>
+where
+    T: DrawTarget,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.target.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.target.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.target.clear(color)
+    }
+}
+
+impl<T, C> Dimensions for Throttled<T, C>
+where
+    T: Dimensions,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<T, C> DisplayMapping for Throttled<T, C>
+where
+    T: DisplayMapping,
+{
+    fn map_from_display(&self, point: Point) -> Option<Point> {
+        self.target.map_from_display(point)
+    }
+
+    fn map_to_display(&self, point: Point) -> Option<Point> {
+        self.target.map_to_display(point)
+    }
+}
+
+impl<T, C> Flushable for Throttled<T, C>
+where
+    T: Flushable,
+    C: Clock,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_with(Refresh::Partial)
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        let refresh = if self.pending == Some(Refresh::Full) {
+            Refresh::Full
+        } else {
+            refresh
+        };
+
+        let now = self.clock.now();
+
+        if now >= self.due_at() {
+            self.flush_now(now, refresh)
+        } else {
+            trace!("Flush held back until {:?}", self.due_at());
+
+            self.pending = Some(refresh);
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use core::time::Duration;
+
+    use embedded_graphics::pixelcolor::BinaryColor;
+    use embedded_graphics::prelude::{DrawTarget, OriginDimensions, Size};
+    use embedded_graphics::Pixel;
+
+    use super::{Clock, Flushable, Refresh, Throttled};
+
+    struct TestClock<'a>(&'a Cell<Duration>);
+
+    impl<'a> Clock for TestClock<'a> {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        partial: usize,
+        full: usize,
+    }
+
+    impl OriginDimensions for Counter {
+        fn size(&self) -> Size {
+            Size::new(1, 1)
+        }
+    }
+
+    impl DrawTarget for Counter {
+        type Error = Infallible;
+
+        type Color = BinaryColor;
+
+        fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            Ok(())
+        }
+    }
+
+    impl Flushable for Counter {
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flush_with(Refresh::Partial)
+        }
+
+        fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+            match refresh {
+                Refresh::Partial => self.partial += 1,
+                Refresh::Full => self.full += 1,
+            }
+
+            Ok(())
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn coalesces_flushes_within_the_interval() {
+        let now = Cell::new(ms(1000));
+        let mut throttled = Throttled::new(Counter::default(), TestClock(&now), 10);
+
+        assert_eq!(throttled.interval(), ms(100));
+
+        throttled.flush().unwrap();
+        assert_eq!(throttled.inner().partial, 1);
+
+        for step in 1..10 {
+            now.set(ms(1000 + step * 10));
+            throttled.flush().unwrap();
+        }
+
+        assert_eq!(throttled.inner().partial, 1);
+        assert!(throttled.is_pending());
+        assert_eq!(throttled.next_flush_at(), Some(ms(1100)));
+
+        now.set(ms(1100));
+        throttled.flush().unwrap();
+
+        assert_eq!(throttled.inner().partial, 2);
+        assert!(!throttled.is_pending());
+    }
+
+    #[test]
+    fn performs_the_deferred_flush_on_poll() {
+        let now = Cell::new(ms(1000));
+        let mut throttled = Throttled::new(Counter::default(), TestClock(&now), 10);
+
+        throttled.flush().unwrap();
+
+        now.set(ms(1020));
+        throttled.flush_with(Refresh::Full).unwrap();
+        now.set(ms(1040));
+        throttled.flush().unwrap();
+
+        assert!(!throttled.poll_flush(ms(1099)).unwrap());
+        assert_eq!(throttled.inner().full, 0);
+
+        assert!(throttled.poll_flush(ms(1100)).unwrap());
+        assert_eq!(throttled.inner().full, 1);
+        assert_eq!(throttled.inner().partial, 1);
+
+        assert!(!throttled.poll_flush(ms(1300)).unwrap());
+        assert_eq!(throttled.next_flush_at(), None);
+    }
+
+    #[test]
+    fn limits_the_flush_rate() {
+        let now = Cell::new(ms(1000));
+        let mut throttled = Throttled::new(Counter::default(), TestClock(&now), 20);
+
+        for step in 0..100 {
+            now.set(ms(1000 + step * 10));
+            throttled.flush().unwrap();
+        }
+
+        assert_eq!(throttled.inner().partial, 20);
+
+        throttled.set_max_flushes_per_sec(0);
+        assert_eq!(throttled.interval(), Duration::ZERO);
+
+        for _ in 0..5 {
+            throttled.flush().unwrap();
+        }
+
+        assert_eq!(throttled.inner().partial, 25);
+    }
+}
@@ -0,0 +1,371 @@
+use core::convert::Infallible;
+
+use embedded_graphics::prelude::{Dimensions, DrawTarget, PixelColor, Point, PointsIter, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use super::{Flushable, Refresh};
+
+pub struct Tiled<'a, T>
+where
+    T: DrawTarget,
+{
+    buf: &'a mut [T::Color],
+    target: T,
+}
+
+impl<'a, T> Tiled<'a, T>
+where
+    T: DrawTarget,
+{
+    pub(crate) fn new(strip_buf: &'a mut [T::Color], target: T) -> Self {
+        let width = target.bounding_box().size.width as usize;
+
+        assert!(
+            width > 0 && strip_buf.len() >= width,
+            "The strip buffer should fit at least one row of the display"
+        );
+
+        Self {
+            buf: strip_buf,
+            target,
+        }
+    }
+
+    pub fn strip_height(&self) -> u32 {
+        (self.buf.len() / self.target.bounding_box().size.width as usize) as _
+    }
+
+    pub fn render<F, E>(&mut self, mut draw: F) -> Result<(), E>
+    where
+        T::Color: Default,
+        F: FnMut(&mut Strip<'_, T::Color>) -> Result<(), E>,
+        E: From<T::Error>,
+    {
+        let bbox = self.target.bounding_box();
+        let strip_height = self.strip_height();
+
+        for y in (0..bbox.size.height).step_by(strip_height as _) {
+            let area = Rectangle::new(
+                bbox.top_left + Point::new(0, y as _),
+                Size::new(bbox.size.width, strip_height.min(bbox.size.height - y)),
+            );
+
+            let len = (area.size.width * area.size.height) as usize;
+
+            // Strips share the buffer, so each one starts out cleared
+            self.buf[..len].fill(T::Color::default());
+
+            let mut strip = Strip {
+                buf: &mut self.buf[..len],
+                area,
+                bbox,
+            };
+
+            draw(&mut strip)?;
+
+            self.target
+                .fill_contiguous(&area, self.buf[..len].iter().copied())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn target(&self) -> &T {
+        &self.target
+    }
+
+    pub fn target_mut(&mut self) -> &mut T {
+        &mut self.target
+    }
+
+    pub fn into_target(self) -> T {
+        self.target
+    }
+}
+
+impl<'a, T> Tiled<'a, T>
+where
+    T: Flushable,
+{
+    pub fn flush(&mut self) -> Result<(), T::Error> {
+        self.target.flush()
+    }
+
+    pub fn flush_with(&mut self, refresh: Refresh) -> Result<(), T::Error> {
+        self.target.flush_with(refresh)
+    }
+}
+
+pub struct Strip<'a, C> {
+    buf: &'a mut [C],
+    area: Rectangle,
+    bbox: Rectangle,
+}
+
+impl<'a, C> Strip<'a, C> {
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    fn offset(&self, point: Point) -> Option<usize> {
+        if self.area.contains(point) {
+            let point = point - self.area.top_left;
+
+            Some(point.y as usize * self.area.size.width as usize + point.x as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, C> DrawTarget for Strip<'a, C>
+where
+    C: PixelColor,
+{
+    type Error = Infallible;
+    type Color = C;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            if let Some(offset) = self.offset(pixel.0) {
+                self.buf[offset] = pixel.1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let mut colors = colors.into_iter();
+
+        let skip_rows = (self.area.top_left.y - area.top_left.y).clamp(0, area.size.height as _);
+        if let Some(skip) = (area.size.width * skip_rows as u32).checked_sub(1) {
+            colors.nth(skip as _);
+        }
+
+        let area = Rectangle::new(
+            area.top_left + Point::new(0, skip_rows),
+            Size::new(area.size.width, area.size.height - skip_rows as u32),
+        );
+
+        let area_bottom = self.area.top_left.y + self.area.size.height as i32;
+
+        for (point, color) in area.points().zip(colors) {
+            if point.y >= area_bottom {
+                break;
+            }
+
+            if let Some(offset) = self.offset(point) {
+                self.buf[offset] = color;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.area);
+        let width = area.size.width as usize;
+
+        for y in area.rows() {
+            if let Some(start) = self.offset(Point::new(area.top_left.x, y)) {
+                self.buf[start..start + width].fill(color);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.buf.fill(color);
+
+        Ok(())
+    }
+}
+
+impl<'a, C> Dimensions for Strip<'a, C> {
+    fn bounding_box(&self) -> Rectangle {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_graphics::pixelcolor::raw::RawU16;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, OriginDimensions, Point, PointsIter, RgbColor, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use crate::draw_target::OwnedDrawTargetExt;
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum Error {
+        Display,
+        Scene,
+    }
+
+    impl From<Infallible> for Error {
+        fn from(err: Infallible) -> Self {
+            match err {}
+        }
+    }
+
+    struct Display {
+        pixels: [[Rgb565; 4]; 5],
+        strips: usize,
+        fail_at: Option<usize>,
+    }
+
+    impl OriginDimensions for Display {
+        fn size(&self) -> Size {
+            Size::new(4, 5)
+        }
+    }
+
+    impl DrawTarget for Display {
+        type Color = Rgb565;
+        type Error = Error;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                self.pixels[point.y as usize][point.x as usize] = color;
+            }
+
+            Ok(())
+        }
+
+        fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Self::Color>,
+        {
+            if self.fail_at == Some(self.strips) {
+                return Err(Error::Display);
+            }
+
+            self.strips += 1;
+
+            self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            )
+        }
+    }
+
+    fn display() -> Display {
+        Display {
+            pixels: [[Rgb565::WHITE; 4]; 5],
+            strips: 0,
+            fail_at: None,
+        }
+    }
+
+    fn diagonal(index: i32) -> Rgb565 {
+        Rgb565::from(RawU16::new(index as u16 + 1))
+    }
+
+    #[test]
+    fn renders_the_scene_in_cleared_strips() {
+        let mut buf = [Rgb565::GREEN; 8];
+        let mut tiled = display().owned_tiled(&mut buf);
+
+        assert_eq!(tiled.strip_height(), 2);
+
+        let mut areas = [None; 4];
+        let mut count = 0;
+
+        tiled
+            .render(|strip| {
+                assert_eq!(strip.bounding_box().size, Size::new(4, 5));
+
+                // Only the first strip is cleared by the scene, the others have to start out cleared
+                if count == 0 {
+                    strip.clear(Rgb565::RED)?;
+                }
+
+                areas[count] = Some(strip.area());
+                count += 1;
+
+                strip.draw_iter(
+                    (0..5).map(|index| Pixel(Point::new(index, index), diagonal(index))),
+                )?;
+
+                Ok::<_, Error>(())
+            })
+            .unwrap();
+
+        assert_eq!(
+            areas,
+            [
+                Some(Rectangle::new(Point::new(0, 0), Size::new(4, 2))),
+                Some(Rectangle::new(Point::new(0, 2), Size::new(4, 2))),
+                Some(Rectangle::new(Point::new(0, 4), Size::new(4, 1))),
+                None,
+            ]
+        );
+
+        let display = tiled.into_target();
+
+        for point in display.bounding_box().points() {
+            let expected = if point.x == point.y {
+                diagonal(point.x)
+            } else if point.y < 2 {
+                Rgb565::RED
+            } else {
+                Rgb565::BLACK
+            };
+
+            assert_eq!(display.pixels[point.y as usize][point.x as usize], expected);
+        }
+    }
+
+    #[test]
+    fn stops_at_the_first_error() {
+        let mut buf = [Rgb565::BLACK; 8];
+        let mut tiled = display().owned_tiled(&mut buf);
+
+        let mut count = 0;
+
+        assert_eq!(
+            tiled.render(|_| {
+                count += 1;
+
+                if count == 2 {
+                    Err(Error::Scene)
+                } else {
+                    Ok(())
+                }
+            }),
+            Err(Error::Scene)
+        );
+        assert_eq!(tiled.target().strips, 1);
+
+        tiled.target_mut().fail_at = Some(2);
+        count = 0;
+
+        assert_eq!(
+            tiled.render(|_| {
+                count += 1;
+
+                Ok::<_, Error>(())
+            }),
+            Err(Error::Display)
+        );
+        assert_eq!(count, 2);
+    }
+}
@@ -0,0 +1,739 @@
+use embedded_graphics::prelude::{Dimensions, DrawTarget, Point, PointsIter, Size};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::Pixel;
+
+use embedded_graphics::transform::Transform as _;
+
+use super::{CoordinateMapping, Flushable, Refresh, RotateAngle};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AffineTransform {
+    matrix: [[i64; 3]; 2],
+    divisor: i64,
+    bbox: Rectangle,
+    clip: Rectangle,
+}
+
+impl AffineTransform {
+    pub const fn new(bbox: Rectangle) -> Self {
+        Self {
+            matrix: [[1, 0, 0], [0, 1, 0]],
+            divisor: 1,
+            bbox,
+            clip: bbox,
+        }
+    }
+
+    pub fn rotate(self, angle: RotateAngle) -> Self {
+        let (left, top, width, height) = Self::bounds(&self.bbox);
+
+        let matrix = match angle {
+            RotateAngle::Degrees90 => [[0, -1, left + width], [1, 0, top]],
+            RotateAngle::Degrees180 => [[-1, 0, left + width], [0, -1, top + height]],
+            RotateAngle::Degrees270 => [[0, 1, left], [-1, 0, top + height]],
+        };
+
+        let bbox = Rectangle::new(Point::zero(), angle.transform_size(self.bbox.size));
+
+        self.compose(matrix, 1, bbox)
+    }
+
+    pub fn scale(self, size: Size) -> Self {
+        if size.width == 0 || size.height == 0 {
+            return self.compose([[1, 0, 0], [0, 1, 0]], 1, Rectangle::zero());
+        }
+
+        let (left, top, width, height) = Self::bounds(&self.bbox);
+        let (swidth, sheight) = (size.width as i64, size.height as i64);
+
+        let matrix = [
+            [width * sheight, 0, left * swidth * sheight],
+            [0, height * swidth, top * swidth * sheight],
+        ];
+
+        self.compose(
+            matrix,
+            swidth * sheight,
+            Rectangle::new(Point::zero(), size),
+        )
+    }
+
+    pub fn translate(self, offset: Point) -> Self {
+        let matrix = [[1, 0, offset.x as i64], [0, 1, offset.y as i64]];
+        let bbox = self.bbox.translate(-offset);
+
+        self.compose(matrix, 1, bbox)
+    }
+
+    pub fn crop(self, area: &Rectangle) -> Self {
+        let area = area.intersection(&self.bbox);
+
+        let matrix = [
+            [1, 0, area.top_left.x as i64],
+            [0, 1, area.top_left.y as i64],
+        ];
+        let bbox = Rectangle::new(Point::zero(), area.size);
+
+        self.compose(matrix, 1, bbox)
+    }
+
+    pub fn clip(mut self, area: &Rectangle) -> Self {
+        let area = area.intersection(&self.bbox);
+
+        self.clip = self.map_rect(&area).intersection(&self.clip);
+        self.bbox = area;
+
+        self
+    }
+
+    pub fn bounding_box(&self) -> Rectangle {
+        self.bbox
+    }
+
+    pub fn clip_area(&self) -> Rectangle {
+        self.clip
+    }
+
+    pub fn transform_point(&self, point: Point) -> Option<Point> {
+        let point = self.map_point(point);
+
+        self.clip.contains(point).then_some(point)
+    }
+
+    pub fn transform_rect(&self, rect: &Rectangle) -> Rectangle {
+        self.map_rect(rect).intersection(&self.clip)
+    }
+
+    pub(crate) fn upscales(&self) -> bool {
+        // A logical pixel covers more than one physical pixel along some axis
+        self.matrix
+            .iter()
+            .any(|row| row[0].abs().max(row[1].abs()) > self.divisor)
+    }
+
+    pub(crate) fn transform_pixel(&self, point: Point) -> Rectangle {
+        self.transform_rect(&Rectangle::new(point, Size::new(1, 1)))
+    }
+
+    pub(crate) fn offset(&self) -> Option<Point> {
+        let [[a, b, x], [c, d, y]] = self.matrix;
+        let divisor = self.divisor;
+
+        (a == divisor && b == 0 && c == 0 && d == divisor && x % divisor == 0 && y % divisor == 0)
+            .then(|| Point::new((x / divisor) as _, (y / divisor) as _))
+    }
+
+    fn map_point(&self, point: Point) -> Point {
+        // Map the center of the pixel and then take the pixel that contains it
+        let x = 2 * point.x as i64 + 1;
+        let y = 2 * point.y as i64 + 1;
+
+        let [[a, b, tx], [c, d, ty]] = self.matrix;
+        let divisor = 2 * self.divisor;
+
+        Point::new(
+            (a * x + b * y + 2 * tx).div_euclid(divisor) as _,
+            (c * x + d * y + 2 * ty).div_euclid(divisor) as _,
+        )
+    }
+
+    fn unmap_point(&self, point: Point) -> Option<Point> {
+        let [[a, b, tx], [c, d, ty]] = self.matrix;
+
+        let det = a * d - b * c;
+        if det == 0 {
+            return None;
+        }
+
+        let x = self.divisor * (2 * point.x as i64 + 1) - 2 * tx;
+        let y = self.divisor * (2 * point.y as i64 + 1) - 2 * ty;
+
+        let divisor = 2 * det.abs();
+        let sign = det.signum();
+
+        Some(Point::new(
+            (sign * (d * x - b * y)).div_euclid(divisor) as _,
+            (sign * (a * y - c * x)).div_euclid(divisor) as _,
+        ))
+    }
+
+    fn map_rect(&self, rect: &Rectangle) -> Rectangle {
+        if rect.is_zero_sized() {
+            return Rectangle::new(self.map_point(rect.top_left), Size::zero());
+        }
+
+        let x1 = rect.top_left.x as i64;
+        let y1 = rect.top_left.y as i64;
+        let x2 = x1 + rect.size.width as i64;
+        let y2 = y1 + rect.size.height as i64;
+
+        let [[a, b, tx], [c, d, ty]] = self.matrix;
+
+        let xs = [a * x1 + b * y1 + tx, a * x2 + b * y2 + tx];
+        let ys = [c * x1 + d * y1 + ty, c * x2 + d * y2 + ty];
+
+        // A physical pixel is covered when its center is covered, and the logical rectangle
+        // is half-open so a rotated or mirrored axis keeps the center on its far edge
+        let covered = |[from, to]: [i64; 2]| {
+            if from <= to {
+                (
+                    Self::center_ceil(from, self.divisor),
+                    Self::center_ceil(to, self.divisor),
+                )
+            } else {
+                (
+                    Self::center_floor(to, self.divisor) + 1,
+                    Self::center_floor(from, self.divisor) + 1,
+                )
+            }
+        };
+
+        let ((x, xend), (y, yend)) = (covered(xs), covered(ys));
+
+        Rectangle::new(
+            Point::new(x as _, y as _),
+            Size::new((xend - x).max(0) as _, (yend - y).max(0) as _),
+        )
+    }
+
+    fn compose(self, matrix: [[i64; 3]; 2], divisor: i64, bbox: Rectangle) -> Self {
+        let m = self.matrix;
+
+        let mut composed = [[0; 3]; 2];
+
+        for (row, composed_row) in composed.iter_mut().enumerate() {
+            for (col, value) in composed_row.iter_mut().enumerate() {
+                *value = m[row][0] * matrix[0][col] + m[row][1] * matrix[1][col];
+            }
+
+            composed_row[2] += m[row][2] * divisor;
+        }
+
+        let mut divisor = self.divisor * divisor;
+
+        let gcd = composed
+            .iter()
+            .flatten()
+            .fold(divisor, |gcd, value| Self::gcd(gcd, *value));
+
+        if gcd > 1 {
+            composed
+                .iter_mut()
+                .flatten()
+                .for_each(|value| *value /= gcd);
+
+            divisor /= gcd;
+        }
+
+        Self {
+            matrix: composed,
+            divisor,
+            bbox,
+            clip: self.clip,
+        }
+    }
+
+    fn bounds(rect: &Rectangle) -> (i64, i64, i64, i64) {
+        (
+            rect.top_left.x as i64,
+            rect.top_left.y as i64,
+            rect.size.width as i64,
+            rect.size.height as i64,
+        )
+    }
+
+    fn center_ceil(value: i64, divisor: i64) -> i64 {
+        // ceil(value / divisor - 1/2)
+        -((divisor - 2 * value).div_euclid(2 * divisor))
+    }
+
+    fn center_floor(value: i64, divisor: i64) -> i64 {
+        // floor(value / divisor - 1/2)
+        (2 * value - divisor).div_euclid(2 * divisor)
+    }
+
+    fn gcd(a: i64, b: i64) -> i64 {
+        let (mut a, mut b) = (a.abs(), b.abs());
+
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+
+        a
+    }
+}
+
+impl CoordinateMapping for AffineTransform {
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        if self.clip.contains(point) {
+            self.unmap_point(point)
+                .filter(|point| self.bbox.contains(*point))
+        } else {
+            None
+        }
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        if self.bbox.contains(point) {
+            self.transform_point(point)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Transformed<'a, T>
+where
+    T: DrawTarget,
+{
+    parent: &'a mut T,
+    transform: AffineTransform,
+}
+
+impl<'a, T> Transformed<'a, T>
+where
+    T: DrawTarget,
+{
+    pub(crate) fn new(parent: &'a mut T, transform: &AffineTransform) -> Self {
+        Self {
+            parent,
+            transform: *transform,
+        }
+    }
+}
+
+impl<'a, T> DrawTarget for Transformed<'a, T>
+where
+    T: DrawTarget,
+{
+    type Error = T::Error;
+    type Color = T::Color;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let transform = &self.transform;
+
+        if transform.upscales() {
+            for Pixel(point, color) in pixels {
+                self.parent
+                    .fill_solid(&transform.transform_pixel(point), color)?;
+            }
+
+            return Ok(());
+        }
+
+        self.parent
+            .draw_iter(pixels.into_iter().filter_map(|pixel| {
+                transform
+                    .transform_point(pixel.0)
+                    .map(|point| Pixel(point, pixel.1))
+            }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let transform = &self.transform;
+
+        if let Some(offset) = transform.offset() {
+            let area = area.translate(offset);
+
+            if transform.clip.intersection(&area) == area {
+                return self.parent.fill_contiguous(&area, colors);
+            }
+        } else if transform.upscales() {
+            for (point, color) in area.points().zip(colors) {
+                self.parent
+                    .fill_solid(&transform.transform_pixel(point), color)?;
+            }
+
+            return Ok(());
+        }
+
+        self.parent.draw_iter(
+            area.points().zip(colors).filter_map(|(pos, color)| {
+                transform.transform_point(pos).map(|pos| Pixel(pos, color))
+            }),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.transform.transform_rect(area);
+
+        self.parent.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let area = self.transform.transform_rect(&self.transform.bbox);
+
+        self.parent.fill_solid(&area, color)
+    }
+}
+
+impl<'a, T> Dimensions for Transformed<'a, T>
+where
+    T: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.transform.bbox
+    }
+}
+
+impl<'a, T> CoordinateMapping for Transformed<'a, T>
+where
+    T: DrawTarget,
+{
+    fn map_to_logical(&self, point: Point) -> Option<Point> {
+        self.transform.map_to_logical(point)
+    }
+
+    fn map_to_physical(&self, point: Point) -> Option<Point> {
+        self.transform.map_to_physical(point)
+    }
+}
+
+impl<'a, T> Flushable for Transformed<'a, T>
+where
+    T: Flushable,
+{
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.parent.flush()
+    }
+
+    fn flush_with(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        self.parent.flush_with(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+
+    use embedded_graphics::pixelcolor::raw::RawU16;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{
+        Dimensions, DrawTarget, DrawTargetExt, OriginDimensions, Point, PointsIter, RawData, Size,
+    };
+    use embedded_graphics::primitives::Rectangle;
+    use embedded_graphics::Pixel;
+
+    use super::{AffineTransform, CoordinateMapping, RotateAngle};
+    use crate::draw_target::DrawTargetExt2;
+
+    #[derive(Default)]
+    struct Grid {
+        pixels: [[u16; 6]; 4],
+        contiguous: usize,
+    }
+
+    impl OriginDimensions for Grid {
+        fn size(&self) -> Size {
+            Size::new(6, 4)
+        }
+    }
+
+    impl DrawTarget for Grid {
+        type Error = Infallible;
+
+        type Color = Rgb565;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for Pixel(point, color) in pixels {
+                if self.bounding_box().contains(point) {
+                    self.pixels[point.y as usize][point.x as usize] =
+                        RawU16::from(color).into_inner();
+                }
+            }
+
+            Ok(())
+        }
+
+        fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Self::Color>,
+        {
+            self.contiguous += 1;
+
+            self.draw_iter(
+                area.points()
+                    .zip(colors)
+                    .map(|(point, color)| Pixel(point, color)),
+            )
+        }
+    }
+
+    #[derive(Copy, Clone, Debug)]
+    enum Op {
+        Iter,
+        Contiguous,
+        Solid,
+    }
+
+    fn color(index: u16) -> Rgb565 {
+        Rgb565::from(RawU16::new(index))
+    }
+
+    fn draw<D>(target: &mut D, op: Op)
+    where
+        D: DrawTarget<Color = Rgb565, Error = Infallible>,
+    {
+        let area = target.bounding_box();
+
+        match op {
+            Op::Iter => target
+                .draw_iter(
+                    area.points()
+                        .zip(1..)
+                        .map(|(point, index)| Pixel(point, color(index))),
+                )
+                .unwrap(),
+            Op::Contiguous => target.fill_contiguous(&area, (1..).map(color)).unwrap(),
+            Op::Solid => target.fill_solid(&area, color(7)).unwrap(),
+        }
+    }
+
+    fn assert_same<C, T>(chain: C, transform: T)
+    where
+        C: Fn(&mut Grid, Op),
+        T: Fn(Rectangle) -> AffineTransform,
+    {
+        for op in [Op::Iter, Op::Contiguous, Op::Solid] {
+            let mut expected = Grid::default();
+            chain(&mut expected, op);
+
+            let mut actual = Grid::default();
+            let transform = transform(actual.bounding_box());
+            draw(&mut actual.transformed(&transform), op);
+
+            assert_eq!(actual.pixels, expected.pixels, "{op:?}");
+        }
+    }
+
+    #[test]
+    fn matches_rotated_and_scaled_chains() {
+        // Upscaling by 2 and downscaling by 3 never put a pixel center on a pixel edge, so the
+        // chains cannot round differently than the composed transform
+        let sizes = |size: Size| {
+            [
+                size / 2,
+                size * 3,
+                Size::new(size.width * 3, size.height / 2),
+            ]
+        };
+
+        for angle in [
+            RotateAngle::Degrees90,
+            RotateAngle::Degrees180,
+            RotateAngle::Degrees270,
+        ] {
+            assert_same(
+                |grid, op| draw(&mut grid.rotated(angle), op),
+                |bbox| AffineTransform::new(bbox).rotate(angle),
+            );
+
+            for size in sizes(angle.transform_size(Size::new(6, 4))) {
+                assert_same(
+                    |grid, op| draw(&mut grid.rotated(angle).scaled(size), op),
+                    |bbox| AffineTransform::new(bbox).rotate(angle).scale(size),
+                );
+            }
+
+            for size in sizes(Size::new(6, 4)) {
+                assert_same(
+                    |grid, op| draw(&mut grid.scaled(size).rotated(angle), op),
+                    |bbox| AffineTransform::new(bbox).scale(size).rotate(angle),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_translated_and_cropped_chains() {
+        let area = Rectangle::new(Point::new(-1, 0), Size::new(4, 3));
+
+        assert_same(
+            |grid, op| draw(&mut grid.translated(Point::new(2, 1)).cropped(&area), op),
+            |bbox| {
+                AffineTransform::new(bbox)
+                    .translate(Point::new(2, 1))
+                    .crop(&area)
+            },
+        );
+        assert_same(
+            |grid, op| {
+                draw(
+                    &mut grid
+                        .cropped(&area)
+                        .rotated(RotateAngle::Degrees270)
+                        .scaled(Size::new(2, 1)),
+                    op,
+                )
+            },
+            |bbox| {
+                AffineTransform::new(bbox)
+                    .crop(&area)
+                    .rotate(RotateAngle::Degrees270)
+                    .scale(Size::new(2, 1))
+            },
+        );
+        assert_same(
+            |grid, op| draw(&mut grid.rotated(RotateAngle::Degrees90).clipped(&area), op),
+            |bbox| {
+                AffineTransform::new(bbox)
+                    .rotate(RotateAngle::Degrees90)
+                    .clip(&area)
+            },
+        );
+    }
+
+    #[test]
+    fn forwards_pure_offsets_contiguously() {
+        let mut grid = Grid::default();
+        let transform = AffineTransform::new(grid.bounding_box())
+            .translate(Point::new(-1, 1))
+            .crop(&Rectangle::new(Point::new(2, 0), Size::new(3, 2)));
+
+        assert_eq!(transform.offset(), Some(Point::new(1, 1)));
+
+        draw(&mut grid.transformed(&transform), Op::Contiguous);
+
+        assert_eq!(grid.contiguous, 1);
+        assert_eq!(grid.pixels[1][1..4], [1, 2, 3]);
+        assert_eq!(grid.pixels[2][1..4], [4, 5, 6]);
+
+        let transform = AffineTransform::new(grid.bounding_box()).rotate(RotateAngle::Degrees180);
+
+        assert_eq!(transform.offset(), None);
+    }
+
+    #[test]
+    fn round_trips_through_unmap() {
+        let bbox = Rectangle::new(Point::new(3, -2), Size::new(6, 4));
+
+        let exact = AffineTransform::new(bbox)
+            .translate(Point::new(1, 2))
+            .rotate(RotateAngle::Degrees90)
+            .crop(&Rectangle::new(Point::new(1, 1), Size::new(3, 4)))
+            .rotate(RotateAngle::Degrees270);
+
+        for point in exact.bounding_box().points() {
+            let physical = exact.map_to_physical(point).unwrap();
+
+            assert!(bbox.contains(physical));
+            assert_eq!(exact.map_to_logical(physical), Some(point));
+        }
+
+        let upscaled = AffineTransform::new(bbox)
+            .rotate(RotateAngle::Degrees180)
+            .scale(Size::new(3, 2));
+
+        for point in upscaled.bounding_box().points() {
+            let block = upscaled.transform_pixel(point);
+
+            assert_eq!(block.size, Size::new(2, 2));
+
+            for physical in block.points() {
+                assert_eq!(upscaled.map_to_logical(physical), Some(point));
+            }
+        }
+
+        let downscaled = AffineTransform::new(bbox).scale(Size::new(12, 8));
+
+        for physical in bbox.points() {
+            let point = downscaled.map_to_logical(physical).unwrap();
+
+            assert_eq!(downscaled.map_to_physical(point), Some(physical));
+        }
+    }
+
+    #[test]
+    fn rectangles_cover_the_pixels_mapped_into_them() {
+        let bbox = Rectangle::new(Point::new(-1, 2), Size::new(6, 4));
+
+        for angle in [
+            RotateAngle::Degrees90,
+            RotateAngle::Degrees180,
+            RotateAngle::Degrees270,
+        ] {
+            for size in [
+                Size::new(12, 8),
+                Size::new(3, 2),
+                Size::new(8, 3),
+                Size::new(4, 6),
+            ] {
+                for transform in [
+                    AffineTransform::new(bbox).rotate(angle).scale(size),
+                    AffineTransform::new(bbox).scale(size).rotate(angle),
+                ] {
+                    let logical = transform.bounding_box();
+
+                    for rect in [
+                        logical,
+                        logical.offset(-1),
+                        Rectangle::new(Point::new(1, 0), Size::new(1, 3)),
+                        Rectangle::new(Point::new(0, 1), Size::new(3, 1)),
+                    ] {
+                        let covered = transform.transform_rect(&rect);
+
+                        for physical in bbox.points() {
+                            assert_eq!(
+                                covered.contains(physical),
+                                transform
+                                    .map_to_logical(physical)
+                                    .map_or(false, |point| rect.contains(point)),
+                                "{physical:?} {rect:?} {transform:?}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clips_to_the_clip_area() {
+        let bbox = Rectangle::new(Point::zero(), Size::new(6, 4));
+        let clip = Rectangle::new(Point::new(1, 1), Size::new(2, 5));
+
+        let transform = AffineTransform::new(bbox)
+            .rotate(RotateAngle::Degrees90)
+            .clip(&clip);
+
+        assert_eq!(transform.bounding_box(), clip);
+        assert_eq!(
+            transform.clip_area(),
+            Rectangle::new(Point::new(0, 1), Size::new(5, 2))
+        );
+
+        assert_eq!(transform.map_to_physical(Point::new(0, 0)), None);
+        assert_eq!(transform.map_to_logical(Point::new(0, 0)), None);
+        assert_eq!(
+            transform.transform_rect(&Rectangle::new(Point::zero(), Size::new(4, 6))),
+            transform.clip_area()
+        );
+    }
+
+    #[test]
+    fn composes_without_overflowing() {
+        let big = Rectangle::new(Point::zero(), Size::new(60_000, 60_000));
+        let transform = AffineTransform::new(big)
+            .translate(Point::new(-30_000, 0))
+            .scale(Size::new(40_000, 40_000));
+
+        assert_eq!(
+            transform.map_to_physical(Point::new(39_999, 39_999)),
+            Some(Point::new(59_999, 59_999))
+        );
+        assert_eq!(
+            transform.map_to_logical(Point::new(59_999, 59_999)),
+            Some(Point::new(39_999, 39_999))
+        );
+    }
+}
@@ -1,3 +1,12 @@
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+mod fmt;
+
 pub mod draw_target;
+
+#[doc(hidden)]
+pub use embedded_graphics as __embedded_graphics;